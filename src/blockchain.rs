@@ -3,10 +3,8 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::Duration;
 
-use web3::types::BlockNumber;
-
 use crate::client::RpcClient;
-use crate::config::{Config, StartBlock};
+use crate::config::{NetworkConfig, StartBlock};
 use crate::error::{Error, Result};
 
 /// Represents the reason why the sleep cycle in `fn sleep_or_ctrlc()` ended.
@@ -62,13 +60,34 @@ impl<'a> BlockchainIter<'a> {
     /// Returns an `Error::StartBlockExceedsLastBlockMined` if the `start_block` that the user
     /// passed in via a CLI argument is in the future (i.e. is greater than the block number of the
     /// most recently mined block).
-    pub fn new(client: &'a RpcClient, config: &Config, running: Arc<AtomicBool>) -> Result<Self> {
+    pub fn new(
+        client: &'a RpcClient,
+        config: &NetworkConfig,
+        running: Arc<AtomicBool>,
+    ) -> Result<Self> {
         let last_mined_block = client.get_last_mined_block_number()?;
+
+        // If no blocks have been mined since the checkpoint was written, there is nothing left to
+        // process in the first block-window; we skip straight to waiting for new blocks.
+        if let StartBlock::Resume(last_processed_block) = config.start_block {
+            if last_processed_block >= last_mined_block {
+                return Ok(BlockchainIter {
+                    client,
+                    start_block: last_processed_block,
+                    stop_block: last_processed_block,
+                    on_first_iteration: false,
+                    block_time: config.block_time,
+                    running,
+                });
+            }
+        }
+
         let start_block = match config.start_block {
             StartBlock::Earliest => 0,
             StartBlock::Latest => last_mined_block,
             StartBlock::Number(block_number) => block_number,
             StartBlock::Tail(tail) => last_mined_block - tail,
+            StartBlock::Resume(last_processed_block) => last_processed_block + 1,
         };
         if start_block > last_mined_block {
             return Err(Error::StartBlockExceedsLastBlockMined {
@@ -88,7 +107,7 @@ impl<'a> BlockchainIter<'a> {
 }
 
 impl<'a> Iterator for BlockchainIter<'a> {
-    type Item = Result<(BlockNumber, BlockNumber)>;
+    type Item = Result<(u64, u64)>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.on_first_iteration {
@@ -106,8 +125,7 @@ impl<'a> Iterator for BlockchainIter<'a> {
             }
         };
         if self.running.load(Ordering::SeqCst) {
-            let range = (self.start_block.into(), self.stop_block.into());
-            Some(Ok(range))
+            Some(Ok((self.start_block, self.stop_block)))
         } else {
             None
         }
@@ -0,0 +1,112 @@
+use serde_json::json;
+
+use crate::error::{Error, Result};
+use crate::notify::Notification;
+use crate::watch::{alert_prefix, WatchedAddress};
+
+/// Formats the chat message posted for a notification. The message only uses formatting that is
+/// rendered the same way by Slack and Mattermost. Messages for ballots that contain an address in
+/// `watch_list` are prefixed with `WATCH_LIST_SUBJECT_PREFIX`.
+pub fn format_message(notif: &Notification, watch_list: &[WatchedAddress]) -> String {
+    let ballot_state = notif.ballot_state();
+    let memo = match ballot_state.memo().trim() {
+        "" => "(none)",
+        memo => memo,
+    };
+    format!(
+        "*{}POA Network Governance Notification: {}*\n\
+         Network: {}\n\
+         Contract: {} ({:?})\n\
+         Ballot ID: {}\n\
+         Ballot Type: {:?}\n\
+         Voting End Time: {}\n\
+         Memo: {}",
+        alert_prefix(watch_list, &ballot_state),
+        notif.event(),
+        notif.network(),
+        notif.contract_name(),
+        notif.contract().version,
+        notif.ballot_id(),
+        notif.ballot_type(),
+        ballot_state.end_time(),
+        memo,
+    )
+}
+
+/// Posts notifications to Slack or Mattermost incoming-webhooks. Both services accept a JSON
+/// payload containing the message in its `text` field.
+#[derive(Debug)]
+pub struct ChatWebhook {
+    client: reqwest::Client,
+    watch_list: Vec<WatchedAddress>,
+}
+
+impl ChatWebhook {
+    pub fn new(watch_list: Vec<WatchedAddress>) -> Self {
+        ChatWebhook {
+            client: reqwest::Client::new(),
+            watch_list,
+        }
+    }
+
+    pub fn post(&self, url: &str, notif: &Notification) -> Result<()> {
+        let payload = json!({ "text": format_message(notif, &self.watch_list) });
+        let resp = self
+            .client
+            .post(url)
+            .json(&payload)
+            .send()
+            .map_err(|e| Error::FailedToPostToWebhook(e))?;
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(Error::InvalidWebhookResponse(resp.status().as_u16()))
+        }
+    }
+}
+
+/// Webhook URLs contain a secret token, so only the URL's host is logged.
+pub fn redact_webhook_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(url) => format!("{}://{}/...", url.scheme(), url.host_str().unwrap_or("")),
+        Err(_) => "<invalid url>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use web3::types::Address;
+
+    use super::{format_message, redact_webhook_url, ChatWebhook};
+    use crate::tests::{sokol_network_config, stand_in_server, threshold_ballot_created};
+    use crate::watch::WatchedAddress;
+
+    #[test]
+    fn test_post_notification_to_webhook() {
+        let config = sokol_network_config();
+        let notif = threshold_ballot_created(&config);
+
+        let (url, rx) = stand_in_server("200 OK", "");
+        let url = format!("{}/hooks/secret-token", url);
+        ChatWebhook::new(vec![]).post(&url, &notif).unwrap();
+        let request = rx.recv().unwrap();
+        assert!(request.starts_with("POST /hooks/secret-token"));
+        assert!(request.contains("Network: Sokol"));
+        assert!(request.contains("Ballot ID: 12"));
+        assert!(request.contains("Ballot Type: Threshold"));
+        assert!(request.contains("Memo: lower the threshold"));
+
+        let (url, _rx) = stand_in_server("500 Internal Server Error", "");
+        assert!(ChatWebhook::new(vec![]).post(&url, &notif).is_err());
+
+        // The ballot's creator is the zero address.
+        assert!(format_message(&notif, &[]).starts_with("*POA Network"));
+        let watched = WatchedAddress { addr: Address::zero(), label: None };
+        assert!(format_message(&notif, &[watched]).starts_with("*[WATCH-LIST ALERT] POA Network"));
+
+        assert_eq!(
+            redact_webhook_url("https://hooks.slack.com/services/T000/B000/XXXX"),
+            "https://hooks.slack.com/...",
+        );
+    }
+}
@@ -1,9 +1,21 @@
 // Some of `Cli`'s methods are not currently being used.
 #![allow(dead_code)]
 
+use std::env;
+use std::ffi::OsString;
+
 use clap::{App, ArgMatches};
 
 pub fn parse_cli() -> Cli {
+    parse_cli_from(env::args_os())
+}
+
+/// Parses the CLI arguments from `args`, the first item is the program's name.
+pub fn parse_cli_from<I, T>(args: I) -> Cli
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
     let cli_args = App::new("poagov")
         .version("2.0.0")
         .about("Monitors a POA Network blockchain for governance events.")
@@ -11,6 +23,7 @@ pub fn parse_cli() -> Cli {
             "[core] --core 'Monitors POA Network's Core Network for governance ballots'
             [sokol] --sokol 'Monitors POA Network's Sokol network for governance ballots'
             [xdai] --xdai 'Monitors the xDai Network for governance ballots'
+            [network] --network [value]... 'Monitors the network with this name for governance ballots (a built-in network or a network defined in the config file), can be given multiple times'
             [keys] -k --keys 'Monitors the blockchain for ballots to change keys'
             [threshold] -t --threshold 'Monitors the blockchain for ballots to change the minimum threshold'
             [proxy] -p --proxy 'Monitors the blockchain for ballots to change the proxy address'
@@ -21,12 +34,28 @@ pub fn parse_cli() -> Cli {
             [latest] --latest 'Monitor for governance events starting at the blockchain's most recently mined block'
             [start_block] --start [value] 'Start monitoring for governance events at this block (inclusive)'
             [tail] --tail [value] 'Start monitoring for governance events for the `n` blocks prior to the last mined block'
-            [email] --email 'Enables email notifications (SMTP configuration options must be set in your `.env` file)'
+            [resume] --resume 'Start monitoring for governance events at the block after the last block processed by a previous run of `poagov`'
+            [config_file] --config [value] 'Reads settings from a TOML config file, CLI arguments take precedence over the config file'
+            [state_dir] --state-dir [value] 'The directory where `poagov` stores its state between runs (defaults to ./state)'
+            [email] --email 'Enables email notifications (SMTP configuration options must be set in your `.env` file or in the `[email]` table of the `--config` file)'
+            [email_digest] --email-digest [value] 'Batches email notifications into one digest email per recipient, sent after each block window (`window`) or once per interval (e.g. 1h, 1d)'
+            [chat] --chat 'Enables chat notifications, posts each notification to the network's Slack or Mattermost incoming-webhooks'
+            [webhook] --webhook 'Enables JSON webhook notifications, posts a signed JSON document for each notification to the configured webhook URLs'
+            [telegram] --telegram 'Enables Telegram notifications, sends each notification to the configured Telegram chats'
             [block_time] --block-time [value] 'The average number of seconds it takes to mine a new block'
             [notification_limit] -n --limit [value] 'Stops `poagov` after this many notifications have been generated (this option can be useful when testing `poagov`)'
+            [output] --output [value] 'The format that notifications are printed to stdout in: `text` (notifications are only logged, the default) or `json` (one JSON object per line)'
             [log_emails] --log-emails 'Logs the full email body for each notification generated, this option does not require the `--email` flag to be set'
+            [votes] --votes 'Generates a notification for each vote cast on a ballot'
+            [vote_digest] --vote-digest 'Generates one notification per ballot for the votes cast on that ballot during each block window'
+            [reminders] --reminders [value] 'Sends a reminder for each open ballot at these offsets before voting ends (a comma-separated list, e.g. 24h,1h)'
+            [finalize_alerts] --finalize-alerts 'Sends an alert when an open v2 ballot can be finalized'
+            [discover_contracts] --discover-contracts 'Reads the voting contract addresses from each network's ProxyStorage contract at startup and periodically, contract addresses that are not configured are discovered'
+            [resend] --resend 'Sends notifications for ballots that have already been notified by a previous run of `poagov`'
+            [list_notified] --list-notified 'Prints each ballot that a notification has been sent for, then exits'
+            [prune_notified] --prune-notified [value] 'Forgets each ballot that a notification was sent for more than `n` days ago, then exits'
             [log_to_file] --log-file 'Logs are written to files in the ./logs directory, logs are rotated chronologically across 3 files, each file has a max size of 8MB'"
-        ).get_matches();
+        ).get_matches_from(args);
 
     Cli(cli_args)
 }
@@ -47,15 +76,17 @@ impl Cli {
         self.0.is_present("xdai")
     }
 
-    pub fn one_network_specified(&self) -> bool {
-        match (self.core(), self.sokol(), self.xdai()) {
-            (true, false, false) => true,
-            (false, true, false) => true,
-            (false, false, true) => true,
-            _ => false,
+    pub fn networks(&self) -> Vec<&str> {
+        match self.0.values_of("network") {
+            Some(names) => names.collect(),
+            None => vec![],
         }
     }
 
+    pub fn no_networks_specified(&self) -> bool {
+        !self.core() && !self.sokol() && !self.xdai() && self.networks().is_empty()
+    }
+
     pub fn keys(&self) -> bool {
         self.0.is_present("keys")
     }
@@ -72,6 +103,10 @@ impl Cli {
         self.0.is_present("emission")
     }
 
+    pub fn config_file(&self) -> Option<&str> {
+        self.0.value_of("config_file")
+    }
+
     pub fn no_contracts_specified(&self) -> bool {
         !self.keys() && !self.threshold() && !self.proxy() && !self.emission()
     }
@@ -84,8 +119,8 @@ impl Cli {
         self.0.is_present("v2")
     }
 
-    pub fn multiple_versions_specified(&self) -> bool {
-        self.v1() && self.v2()
+    pub fn no_versions_specified(&self) -> bool {
+        !self.v1() && !self.v2()
     }
 
     pub fn earliest(&self) -> bool {
@@ -104,20 +139,83 @@ impl Cli {
         self.0.value_of("tail")
     }
 
+    pub fn resume(&self) -> bool {
+        self.0.is_present("resume")
+    }
+
     pub fn one_start_block_was_specified(&self) -> bool {
-        match (self.earliest(), self.latest(), self.start_block().is_some(), self.tail().is_some()) {
-            (true, false, false, false) => true,
-            (false, true, false, false) => true,
-            (false, false, true, false) => true,
-            (false, false, false, true) => true,
+        match (
+            self.earliest(),
+            self.latest(),
+            self.start_block().is_some(),
+            self.tail().is_some(),
+            self.resume(),
+        ) {
+            (true, false, false, false, false) => true,
+            (false, true, false, false, false) => true,
+            (false, false, true, false, false) => true,
+            (false, false, false, true, false) => true,
+            (false, false, false, false, true) => true,
             _ => false,
         }
     }
 
+    pub fn state_dir(&self) -> Option<&str> {
+        self.0.value_of("state_dir")
+    }
+
+    pub fn votes(&self) -> bool {
+        self.0.is_present("votes")
+    }
+
+    pub fn vote_digest(&self) -> bool {
+        self.0.is_present("vote_digest")
+    }
+
+    pub fn reminders(&self) -> Option<&str> {
+        self.0.value_of("reminders")
+    }
+
+    pub fn finalize_alerts(&self) -> bool {
+        self.0.is_present("finalize_alerts")
+    }
+
+    pub fn discover_contracts(&self) -> bool {
+        self.0.is_present("discover_contracts")
+    }
+
+    pub fn resend(&self) -> bool {
+        self.0.is_present("resend")
+    }
+
+    pub fn list_notified(&self) -> bool {
+        self.0.is_present("list_notified")
+    }
+
+    pub fn prune_notified(&self) -> Option<&str> {
+        self.0.value_of("prune_notified")
+    }
+
     pub fn email(&self) -> bool {
         self.0.is_present("email")
     }
 
+    pub fn email_digest(&self) -> Option<&str> {
+        self.0.value_of("email_digest")
+    }
+
+    pub fn chat(&self) -> bool {
+        self.0.is_present("chat")
+    }
+
+    pub fn webhook(&self) -> bool {
+        self.0.is_present("webhook")
+    }
+
+    pub fn telegram(&self) -> bool {
+        self.0.is_present("telegram")
+    }
+
     pub fn block_time(&self) -> Option<&str> {
         self.0.value_of("block_time")
     }
@@ -126,6 +224,10 @@ impl Cli {
         self.0.value_of("notification_limit")
     }
 
+    pub fn output(&self) -> Option<&str> {
+        self.0.value_of("output")
+    }
+
     pub fn log_emails(&self) -> bool {
         self.0.is_present("log_emails")
     }
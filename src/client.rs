@@ -4,17 +4,21 @@ use jsonrpc_core as json_rpc;
 use serde_json as json;
 use web3::types::{Address, BlockNumber, Filter, FilterBuilder, U256};
 
-use crate::config::{ContractType, PoaContract};
+use crate::config::{ContractType, ContractVersion, PoaContract, ProxyStorage};
 use crate::error::{Error, Result};
-use crate::response::common::BallotCreatedLog;
+use crate::response::common::{
+    BallotCanceledLog, BallotCreatedLog, BallotFinalizedLog, BallotState, VoteLog,
+};
 use crate::response::v1::{KeysVotingState, ProxyVotingState, ThresholdVotingState, VotingState};
 use crate::response::v2::{
-    BallotInfo, EmissionBallotInfo, KeysBallotInfo, ProxyBallotInfo, ThresholdBallotInfo,
+    BallotInfo, EmissionBallotInfo, KeysBallotInfo, ProxyBallotInfo, QuorumState,
+    ThresholdBallotInfo,
 };
 
 #[derive(Debug)]
 pub enum RpcMethod {
     CallContractFunction,
+    GetChainId,
     GetLogs,
     GetLastMinedBlockNumber,
 }
@@ -23,6 +27,7 @@ impl Into<String> for RpcMethod {
     fn into(self) -> String {
         let s = match self {
             RpcMethod::CallContractFunction => "eth_call",
+            RpcMethod::GetChainId => "eth_chainId",
             RpcMethod::GetLogs => "eth_getLogs",
             RpcMethod::GetLastMinedBlockNumber => "eth_blockNumber",
         };
@@ -30,6 +35,16 @@ impl Into<String> for RpcMethod {
     }
 }
 
+/// The endpoint is user-supplied and may not be a POA node, so a malformed `eth_chainId`
+/// response is returned as an error rather than panicking.
+fn parse_chain_id(resp: json::Value) -> Result<u64> {
+    match resp {
+        json::Value::String(s) => u64::from_str_radix(s.trim_left_matches("0x"), 16)
+            .map_err(|_| Error::InvalidChainId(s.clone())),
+        resp => Err(Error::InvalidChainId(resp.to_string())),
+    }
+}
+
 #[derive(Debug)]
 pub struct RpcClient {
     endpoint: String,
@@ -67,7 +82,7 @@ impl RpcClient {
             .execute(req)
             .map_err(|e| Error::RequestFailed(e))?
             .json()
-            .unwrap();
+            .map_err(|e| Error::RequestFailed(e))?;
         if let json_rpc::types::response::Response::Single(resp_status) = resp {
             match resp_status {
                 json_rpc::types::response::Output::Success(resp) => return Ok(resp.result),
@@ -89,6 +104,11 @@ impl RpcClient {
         unreachable!("Received a non-string response from `eth_blockNumber` call");
     }
 
+    pub fn get_chain_id(&self) -> Result<u64> {
+        let req = self.build_request(RpcMethod::GetChainId, vec![])?;
+        parse_chain_id(self.send(req)?)
+    }
+
     fn get_logs(&self, filter: Filter) -> Result<Vec<web3::types::Log>> {
         let params = vec![json::to_value(filter).unwrap()];
         let req = self.build_request(RpcMethod::GetLogs, params)?;
@@ -96,14 +116,15 @@ impl RpcClient {
         Ok(json::from_value(result).unwrap())
     }
 
-    /// V1 and V2
-    pub fn get_ballot_created_logs(
+    /// Gets and parses each `event` log emitted by `contract` within the block range
+    /// `start...stop`. Returns each parsed log along with the block number that it was found in.
+    fn get_event_logs(
         &self,
         contract: &PoaContract,
+        event: &ethabi::Event,
         start: BlockNumber,
         stop: BlockNumber,
-    ) -> Result<Vec<BallotCreatedLog>> {
-        let event = contract.event("BallotCreated");
+    ) -> Result<Vec<(ethabi::Log, U256)>> {
         let event_sig = event.signature();
         let filter = FilterBuilder::default()
             .topics(Some(vec![event_sig]), None, None, None)
@@ -124,18 +145,22 @@ impl RpcClient {
                 let ethabi_log = event
                     .parse_log(raw_log)
                     .map_err(|e| Error::FailedToParseRawLogToLog(e))?;
-                BallotCreatedLog::from_ethabi_log(ethabi_log, block_number.unwrap())
+                Ok((ethabi_log, block_number.unwrap()))
             })
             .collect()
     }
 
-    /// V1
-    pub fn get_voting_state(&self, contract: &PoaContract, ballot_id: U256) -> Result<VotingState> {
-        let function = contract.function("votingState");
-        let tokens = vec![ethabi::Token::Uint(ballot_id)];
-        let encoded_input = function.encode_input(&tokens).unwrap();
+    /// Calls the contract `function` deployed at `addr` using the latest block's state, returns
+    /// the function's decoded outputs.
+    fn call_function(
+        &self,
+        addr: Address,
+        function: &ethabi::Function,
+        tokens: &[ethabi::Token],
+    ) -> Result<Vec<ethabi::Token>> {
+        let encoded_input = function.encode_input(tokens).unwrap();
         let function_call_request = web3::types::CallRequest {
-            to: contract.addr,
+            to: addr,
             data: Some(encoded_input.into()),
             from: None,
             gas: None,
@@ -150,84 +175,231 @@ impl RpcClient {
         if let json::Value::String(s) = self.send(req)? {
             let s = s.trim_left_matches("0x");
             let bytes = hex::decode(s).unwrap();
-            let outputs = function.decode_output(&bytes).unwrap();
-            let voting_state: VotingState = match contract.kind {
-                ContractType::Keys => KeysVotingState::from(outputs).into(),
-                ContractType::Threshold => ThresholdVotingState::from(outputs).into(),
-                ContractType::Proxy => ProxyVotingState::from(outputs).into(),
-                ContractType::Emission => return Err(Error::EmissionFundsV1ContractDoesNotExist),
-            };
-            return Ok(voting_state);
+            return Ok(function.decode_output(&bytes).unwrap());
         }
-        unreachable!("received non-string JSON response from `votingState`");
+        unreachable!("received non-string JSON response from `{}`", function.name);
+    }
+
+    /// V1 and V2
+    pub fn get_ballot_created_logs(
+        &self,
+        contract: &PoaContract,
+        start: BlockNumber,
+        stop: BlockNumber,
+    ) -> Result<Vec<BallotCreatedLog>> {
+        let event = contract.event("BallotCreated");
+        self.get_event_logs(contract, &event, start, stop)?
+            .into_iter()
+            .map(|(ethabi_log, block_number)| {
+                BallotCreatedLog::from_ethabi_log(ethabi_log, block_number)
+            })
+            .collect()
+    }
+
+    /// V1 and V2
+    pub fn get_ballot_finalized_logs(
+        &self,
+        contract: &PoaContract,
+        start: BlockNumber,
+        stop: BlockNumber,
+    ) -> Result<Vec<BallotFinalizedLog>> {
+        let event = contract.event("BallotFinalized");
+        self.get_event_logs(contract, &event, start, stop)?
+            .into_iter()
+            .map(|(ethabi_log, block_number)| {
+                BallotFinalizedLog::from_ethabi_log(ethabi_log, block_number)
+            })
+            .collect()
+    }
+
+    /// V2 Emission Funds contract only
+    pub fn get_ballot_canceled_logs(
+        &self,
+        contract: &PoaContract,
+        start: BlockNumber,
+        stop: BlockNumber,
+    ) -> Result<Vec<BallotCanceledLog>> {
+        let event = contract.event("BallotCanceled");
+        self.get_event_logs(contract, &event, start, stop)?
+            .into_iter()
+            .map(|(ethabi_log, block_number)| {
+                BallotCanceledLog::from_ethabi_log(ethabi_log, block_number)
+            })
+            .collect()
+    }
+
+    /// V1 and V2
+    pub fn get_vote_logs(
+        &self,
+        contract: &PoaContract,
+        start: BlockNumber,
+        stop: BlockNumber,
+    ) -> Result<Vec<VoteLog>> {
+        let event = contract.event("Vote");
+        self.get_event_logs(contract, &event, start, stop)?
+            .into_iter()
+            .map(|(ethabi_log, block_number)| {
+                VoteLog::from_ethabi_log(ethabi_log, block_number, contract.kind)
+            })
+            .collect()
+    }
+
+    /// V1 and V2 - gets a ballot's current state using `.votingState()` for V1 contracts and
+    /// `.getBallotInfo()` for V2 contracts.
+    pub fn get_ballot_state(&self, contract: &PoaContract, ballot_id: U256) -> Result<BallotState> {
+        let ballot_state = match contract.version {
+            ContractVersion::V1 => self.get_voting_state(contract, ballot_id)?.into(),
+            ContractVersion::V2 => self.get_ballot_info(contract, ballot_id, None)?.into(),
+        };
+        Ok(ballot_state)
+    }
+
+    /// V1
+    pub fn get_voting_state(&self, contract: &PoaContract, ballot_id: U256) -> Result<VotingState> {
+        let function = contract.function("votingState");
+        let tokens = vec![ethabi::Token::Uint(ballot_id)];
+        let outputs = self.call_function(contract.addr, &function, &tokens)?;
+        let voting_state: VotingState = match contract.kind {
+            ContractType::Keys => KeysVotingState::from(outputs).into(),
+            ContractType::Threshold => ThresholdVotingState::from(outputs).into(),
+            ContractType::Proxy => ProxyVotingState::from(outputs).into(),
+            ContractType::Emission => return Err(Error::EmissionFundsV1ContractDoesNotExist),
+        };
+        Ok(voting_state)
     }
 
     /// V2
-    // TODO: When V2 contracts have been published and ballots have begun, test that calling
-    // `.getBallotInfo()` with `Address::zero()` for the `votingKey` works (we don't care if
-    // `votingKey` has voted yet).
-    pub fn get_ballot_info(&self, contract: &PoaContract, ballot_id: U256) -> Result<BallotInfo> {
-        // pub fn get_ballot_info(&self, contract: &PoaContract, ballot_id: U256, voting_key: Option<Address>) -> Result<BallotInfo> {
+    ///
+    /// The V2 Threshold and Proxy contracts' `.getBallotInfo()` take a `votingKey` argument, which
+    /// is used to determine the returned `already_voted` field. If `voting_key` is `None`, we pass
+    /// in `Address::zero()` (in which case, `already_voted` is meaningless).
+    pub fn get_ballot_info(
+        &self,
+        contract: &PoaContract,
+        ballot_id: U256,
+        voting_key: Option<Address>,
+    ) -> Result<BallotInfo> {
         let function = contract.function("getBallotInfo");
-        /*
         let mut tokens = vec![ethabi::Token::Uint(ballot_id)];
         if function.inputs.len() == 2 {
-            if let Some(voting_key) = voting_key {
-                tokens.push(ethabi::Token::Address(voting_key));
+            let voting_key = voting_key.unwrap_or_else(Address::zero);
+            tokens.push(ethabi::Token::Address(voting_key));
+        }
+        let outputs = self.call_function(contract.addr, &function, &tokens)?;
+        let ballot_info: BallotInfo = match contract.kind {
+            ContractType::Keys => KeysBallotInfo::from(outputs).into(),
+            ContractType::Threshold => ThresholdBallotInfo::from(outputs).into(),
+            ContractType::Proxy => ProxyBallotInfo::from(outputs).into(),
+            ContractType::Emission => EmissionBallotInfo::from(outputs).into(),
+        };
+        Ok(ballot_info)
+    }
+
+    /// V1 and V2 - returns whether or not `voting_key` has voted on the ballot.
+    pub fn has_already_voted(
+        &self,
+        contract: &PoaContract,
+        ballot_id: U256,
+        voting_key: Address,
+    ) -> Result<bool> {
+        let function = contract.function("hasAlreadyVoted");
+        let tokens = vec![ethabi::Token::Uint(ballot_id), ethabi::Token::Address(voting_key)];
+        let outputs = self.call_function(contract.addr, &function, &tokens)?;
+        Ok(outputs[0].clone().to_bool().unwrap())
+    }
+
+    /// V2
+    pub fn get_quorum_state(&self, contract: &PoaContract, ballot_id: U256) -> Result<QuorumState> {
+        let function = contract.function("getQuorumState");
+        let tokens = vec![ethabi::Token::Uint(ballot_id)];
+        let outputs = self.call_function(contract.addr, &function, &tokens)?;
+        let uint = outputs[0].clone().to_uint().unwrap();
+        Ok(QuorumState::new(uint, contract.kind))
+    }
+
+    /// V1 and V2 - returns the ids of the ballots that are open for voting in `contract`.
+    ///
+    /// The Keys, Threshold, and Proxy contracts store a list of their active ballots. The Emission
+    /// Funds contract only allows one ballot to be active at a time, so its most recently created
+    /// ballot is returned if that ballot is still open.
+    pub fn get_open_ballot_ids(&self, contract: &PoaContract) -> Result<Vec<U256>> {
+        if contract.kind == ContractType::Emission {
+            let function = contract.function("nextBallotId");
+            let outputs = self.call_function(contract.addr, &function, &[])?;
+            let next_ballot_id = outputs[0].clone().to_uint().unwrap();
+            if next_ballot_id.is_zero() {
+                return Ok(vec![]);
             }
+            let ballot_id = next_ballot_id - U256::one();
+            let is_open = !self.get_ballot_state(contract, ballot_id)?.is_closed();
+            return Ok(if is_open { vec![ballot_id] } else { vec![] });
         }
-        */
-        let mut tokens = vec![ethabi::Token::Uint(ballot_id)];
-        if function.inputs.len() == 2 {
-            tokens.push(ethabi::Token::Address(Address::zero()));
+        let function = contract.function("activeBallotsLength");
+        let outputs = self.call_function(contract.addr, &function, &[])?;
+        let n_active = outputs[0].clone().to_uint().unwrap().low_u64();
+        let function = contract.function("activeBallots");
+        let mut ballot_ids = vec![];
+        for i in 0..n_active {
+            let tokens = vec![ethabi::Token::Uint(U256::from(i))];
+            let outputs = self.call_function(contract.addr, &function, &tokens)?;
+            ballot_ids.push(outputs[0].clone().to_uint().unwrap());
         }
+        Ok(ballot_ids)
+    }
 
-        let encoded_input = function.encode_input(&tokens).unwrap();
-        let function_call_request = web3::types::CallRequest {
-            to: contract.addr,
-            data: Some(encoded_input.into()),
-            from: None,
-            gas: None,
-            gas_price: None,
-            value: None,
-        };
-        let rpc_method_params = vec![
-            json::to_value(function_call_request).unwrap(),
-            json::to_value(BlockNumber::Latest).unwrap(),
-        ];
-        let req = self.build_request(RpcMethod::CallContractFunction, rpc_method_params)?;
-        if let json::Value::String(s) = self.send(req)? {
-            let s = s.trim_left_matches("0x");
-            let bytes = hex::decode(s).unwrap();
-            let outputs = function.decode_output(&bytes).unwrap();
-            let ballot_info: BallotInfo = match contract.kind {
-                ContractType::Keys => KeysBallotInfo::from(outputs).into(),
-                ContractType::Threshold => ThresholdBallotInfo::from(outputs).into(),
-                ContractType::Proxy => ProxyBallotInfo::from(outputs).into(),
-                ContractType::Emission => EmissionBallotInfo::from(outputs).into(),
-            };
-            return Ok(ballot_info);
-        }
-        unreachable!("received non-string JSON response from `getBallotInfo`");
+    /// V1 and V2 - returns the address of the `ProxyStorage` contract that `contract` belongs to.
+    pub fn get_proxy_storage_addr(&self, contract: &PoaContract) -> Result<Address> {
+        let function = contract.function("proxyStorage");
+        let outputs = self.call_function(contract.addr, &function, &[])?;
+        Ok(outputs[0].clone().to_address().unwrap())
+    }
+
+    /// V1 and V2 - returns the current address of the `contract_type` voting contract stored in
+    /// the `ProxyStorage` contract deployed at `proxy_storage_addr`.
+    pub fn get_voting_contract_addr(
+        &self,
+        proxy_storage: &ProxyStorage,
+        proxy_storage_addr: Address,
+        contract_type: ContractType,
+    ) -> Result<Address> {
+        let function = proxy_storage.function(contract_type.proxy_storage_getter());
+        let outputs = self.call_function(proxy_storage_addr, &function, &[])?;
+        Ok(outputs[0].clone().to_address().unwrap())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::fs::File;
     use std::str::FromStr;
 
+    use serde_json::json;
     use web3::types::{Address, BlockNumber, U256};
 
-    use super::RpcClient;
-    use crate::config::{ContractType, ContractVersion, Network, PoaContract};
+    use super::{parse_chain_id, RpcClient};
+    use crate::config::{ContractType, ContractVersion, Network, PoaContract, ProxyStorage};
+    use crate::error::Error;
     use crate::response::v1::VotingState;
-    use crate::response::v2::BallotInfo;
+    use crate::response::v2::{BallotInfo, QuorumState};
     use crate::tests::{
         setup, SOKOL_NETWORK, V1_CONTRACT_TYPES, V1_VERSION, V2_CONTRACT_TYPES, V2_VERSION,
         XDAI_NETWORK,
     };
 
+    #[test]
+    fn test_malformed_chain_id_is_an_error() {
+        assert_eq!(parse_chain_id(json!("0x4d")).unwrap(), 77);
+        match parse_chain_id(json!("0xnothex")) {
+            Err(Error::InvalidChainId(s)) => assert_eq!(s, "0xnothex"),
+            res => panic!("expected `InvalidChainId`, found: {:?}", res),
+        }
+        match parse_chain_id(json!(null)) {
+            Err(Error::InvalidChainId(s)) => assert_eq!(s, "null"),
+            res => panic!("expected `InvalidChainId`, found: {:?}", res),
+        }
+    }
+
     #[test]
     fn test_get_last_mined_block_core() {
         setup();
@@ -280,7 +452,7 @@ mod tests {
     fn test_get_ballot_created_logs_for_keys_v1_contract() {
         setup();
 
-        let contract = PoaContract::read(ContractType::Keys, SOKOL_NETWORK, V1_VERSION)
+        let contract = PoaContract::read(ContractType::Keys, &SOKOL_NETWORK, V1_VERSION)
             .unwrap_or_else(|e| panic!("Failed to load contract: {:?}", e));
 
         let rpc_url = env::var("SOKOL_RPC_ENDPOINT")
@@ -304,7 +476,7 @@ mod tests {
     fn test_get_ballot_created_logs_for_keys_v2_contract() {
         setup();
 
-        let contract = PoaContract::read(ContractType::Keys, SOKOL_NETWORK, V2_VERSION)
+        let contract = PoaContract::read(ContractType::Keys, &SOKOL_NETWORK, V2_VERSION)
             .unwrap_or_else(|e| panic!("Failed to load contract: {:?}", e));
 
         let rpc_url = env::var("SOKOL_RPC_ENDPOINT")
@@ -328,7 +500,7 @@ mod tests {
     fn test_get_voting_state_for_threshold_v1() {
         setup();
 
-        let contract = PoaContract::read(ContractType::Threshold, SOKOL_NETWORK, V1_VERSION)
+        let contract = PoaContract::read(ContractType::Threshold, &SOKOL_NETWORK, V1_VERSION)
                 .unwrap_or_else(|e| panic!("Failed to load contract: {:?}", e));
 
         let rpc_url = env::var("SOKOL_RPC_ENDPOINT")
@@ -351,15 +523,16 @@ mod tests {
     fn test_get_ballot_info_for_emission_v2() {
         setup();
 
-        let contract = PoaContract::read(ContractType::Emission, Network::Sokol, ContractVersion::V2)
-            .unwrap_or_else(|e| panic!("Failed to load contract: {:?}", e));
+        let contract =
+            PoaContract::read(ContractType::Emission, &Network::SOKOL, ContractVersion::V2)
+                .unwrap_or_else(|e| panic!("Failed to load contract: {:?}", e));
 
         let rpc_url = env::var("SOKOL_RPC_ENDPOINT")
             .expect("Missing env-var: `SOKOL_RPC_ENDPOINT`");
 
         let client = RpcClient::new(rpc_url);
         let ballot_id = U256::from(0);
-        let res = client.get_ballot_info(&contract, ballot_id);
+        let res = client.get_ballot_info(&contract, ballot_id, None);
         assert!(res.is_ok());
 
         if let BallotInfo::Emission(emission_ballot_info) = res.unwrap() {
@@ -372,6 +545,116 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_ballot_canceled_logs_for_emission_v2() {
+        setup();
+
+        let contract =
+            PoaContract::read(ContractType::Emission, &Network::SOKOL, ContractVersion::V2)
+                .unwrap_or_else(|e| panic!("Failed to load contract: {:?}", e));
+
+        let rpc_url = env::var("SOKOL_RPC_ENDPOINT")
+            .expect("Missing env-var: `SOKOL_RPC_ENDPOINT`");
+
+        let client = RpcClient::new(rpc_url);
+        let res = client.get_ballot_canceled_logs(
+            &contract,
+            BlockNumber::Earliest,
+            BlockNumber::Latest,
+        );
+        assert!(res.is_ok());
+
+        // Every ballot that emitted a `BallotCanceled` event is stored as canceled.
+        for log in res.unwrap() {
+            let res = client.get_ballot_info(&contract, log.ballot_id, None);
+            if let BallotInfo::Emission(emission_ballot_info) = res.unwrap() {
+                assert!(emission_ballot_info.is_canceled);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_quorum_state_for_all_v2_contracts() {
+        setup();
+
+        let rpc_url = env::var("SOKOL_RPC_ENDPOINT")
+            .expect("Missing env-var: `SOKOL_RPC_ENDPOINT`");
+
+        let client = RpcClient::new(rpc_url);
+        let ballot_id = U256::from(0);
+
+        // Ballot 0 exists in each of the V2 contracts on the Sokol chain, so its quorum state
+        // should not be `Invalid`.
+        for contract_type in V2_CONTRACT_TYPES.iter() {
+            let contract = match PoaContract::read(*contract_type, &SOKOL_NETWORK, V2_VERSION) {
+                Ok(contract) => contract,
+                Err(e) => panic!("Failed to load contract: {:?}", e),
+            };
+            let res = client.get_quorum_state(&contract, ballot_id);
+            assert!(res.is_ok());
+            if let QuorumState::Invalid = res.unwrap() {
+                panic!("ballot 0 has an invalid quorum state in the {:?} contract", contract_type);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_open_ballot_ids_for_all_v2_contracts() {
+        setup();
+
+        let rpc_url = env::var("SOKOL_RPC_ENDPOINT")
+            .expect("Missing env-var: `SOKOL_RPC_ENDPOINT`");
+
+        let client = RpcClient::new(rpc_url);
+
+        for contract_type in V2_CONTRACT_TYPES.iter() {
+            let contract = match PoaContract::read(*contract_type, &SOKOL_NETWORK, V2_VERSION) {
+                Ok(contract) => contract,
+                Err(e) => panic!("Failed to load contract: {:?}", e),
+            };
+            let res = client.get_open_ballot_ids(&contract);
+            assert!(res.is_ok());
+            for ballot_id in res.unwrap() {
+                let ballot_state = client.get_ballot_state(&contract, ballot_id).unwrap();
+                assert!(!ballot_state.is_closed());
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_voting_contract_addrs_from_proxy_storage_v2() {
+        setup();
+
+        let rpc_url = env::var("SOKOL_RPC_ENDPOINT")
+            .expect("Missing env-var: `SOKOL_RPC_ENDPOINT`");
+
+        let client = RpcClient::new(rpc_url);
+        let keys_contract = PoaContract::read(ContractType::Keys, &SOKOL_NETWORK, V2_VERSION)
+            .unwrap_or_else(|e| panic!("Failed to load contract: {:?}", e));
+        let proxy_storage_addr = client.get_proxy_storage_addr(&keys_contract).unwrap();
+        assert_ne!(proxy_storage_addr, Address::zero());
+
+        let abi_file = File::open("abis/v2/ProxyStorage.abi.json").unwrap();
+        let proxy_storage = ProxyStorage {
+            version: V2_VERSION,
+            addr: Some(proxy_storage_addr),
+            abi: ethabi::Contract::load(&abi_file).unwrap(),
+        };
+
+        // Each of the V2 voting contracts stores the same `ProxyStorage` address, and
+        // `ProxyStorage` stores the address of each of the V2 voting contracts.
+        for contract_type in V2_CONTRACT_TYPES.iter() {
+            let contract = match PoaContract::read(*contract_type, &SOKOL_NETWORK, V2_VERSION) {
+                Ok(contract) => contract,
+                Err(e) => panic!("Failed to load contract: {:?}", e),
+            };
+            assert_eq!(client.get_proxy_storage_addr(&contract).unwrap(), proxy_storage_addr);
+            let res =
+                client.get_voting_contract_addr(&proxy_storage, proxy_storage_addr, *contract_type);
+            assert_eq!(res.unwrap(), contract.addr);
+        }
+    }
+
     #[test]
     fn test_get_voting_state_for_all_v1_contracts() {
         setup();
@@ -383,7 +666,7 @@ mod tests {
         let ballot_id = U256::from(0);
 
         for contract_type in V1_CONTRACT_TYPES.iter() {
-            let contract = match PoaContract::read(*contract_type, SOKOL_NETWORK, V1_VERSION) {
+            let contract = match PoaContract::read(*contract_type, &SOKOL_NETWORK, V1_VERSION) {
                 Ok(contract) => contract,
                 Err(e) => panic!("Failed to load contract: {:?}", e),
             };
@@ -403,11 +686,11 @@ mod tests {
         let ballot_id = U256::from(0);
 
         for contract_type in V2_CONTRACT_TYPES.iter() {
-            let contract = match PoaContract::read(*contract_type, SOKOL_NETWORK, V2_VERSION) {
+            let contract = match PoaContract::read(*contract_type, &SOKOL_NETWORK, V2_VERSION) {
                 Ok(contract) => contract,
                 Err(e) => panic!("Failed to load contract: {:?}", e),
             };
-            let res = client.get_ballot_info(&contract, ballot_id);
+            let res = client.get_ballot_info(&contract, ballot_id, None);
             assert!(res.is_ok());
         }
     }
@@ -423,11 +706,11 @@ mod tests {
         let ballot_id = U256::from(0);
 
         for contract_type in V2_CONTRACT_TYPES.iter() {
-            let contract = match PoaContract::read(*contract_type, XDAI_NETWORK, V2_VERSION) {
+            let contract = match PoaContract::read(*contract_type, &XDAI_NETWORK, V2_VERSION) {
                 Ok(contract) => contract,
                 Err(e) => panic!("Failed to load contract: {:?}", e),
             };
-            let res = client.get_ballot_info(&contract, ballot_id);
+            let res = client.get_ballot_info(&contract, ballot_id, None);
             assert!(res.is_ok());
         }
     }
@@ -1,35 +1,109 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::env;
-use std::fmt::{self, Debug, Formatter};
+use std::fmt::{self, Debug, Display, Formatter};
 use std::fs::File;
 use std::str::FromStr as _FromStr;
+use std::sync::{Arc, Mutex};
 
+use chrono::Duration;
 use ethabi::{Address, Contract, Event, Function};
+use serde_derive::{Deserialize, Serialize};
 
 use crate::cli::Cli;
+use crate::config_file::{
+    ConfigFile, ContractSection, NetworkSection, ProxyStorageSection, SubscriptionSection,
+};
+use crate::email::EmailSink;
 use crate::error::{Error, Result};
+use crate::logger::Logger;
 use crate::response::common::BallotType;
+use crate::sink::{ChatSink, JsonOutputSink, LogSink, Sink, TelegramSink, WebhookSink};
+use crate::state::checkpoint::Checkpoint;
+use crate::state::outbox::DEFAULT_EMAIL_MAX_ATTEMPTS;
+use crate::telegram::{TelegramBot, DEFAULT_TELEGRAM_API_URL};
+use crate::watch::WatchedAddress;
+use crate::webhook::{JsonWebhook, DEFAULT_WEBHOOK_MAX_RETRIES};
 
 const DEFAULT_BLOCK_TIME_SECS: u64 = 30;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum Network {
-    Core,
-    Sokol,
-    XDai,
+// The directory (relative to Cargo.toml) to store `poagov`'s state between runs.
+pub const DEFAULT_STATE_DIR: &str = "state";
+
+// The directory (relative to Cargo.toml) containing the `v1/` and `v2/` contract ABI directories.
+const DEFAULT_ABI_DIR: &str = "abis";
+
+/// A chain running the POA governance contracts. Core, Sokol, and xDai are built into `poagov`,
+/// any other chain (e.g. a private POA testnet) can be defined in the config file.
+#[derive(Clone, Debug)]
+pub struct Network {
+    name: Cow<'static, str>,
 }
 
 impl Network {
-    fn uppercase(&self) -> &str {
-        match self {
-            Network::Core => "CORE",
-            Network::Sokol => "SOKOL",
-            Network::XDai=> "XDAI",
+    pub const CORE: Network = Network { name: Cow::Borrowed("Core") };
+    pub const SOKOL: Network = Network { name: Cow::Borrowed("Sokol") };
+    pub const XDAI: Network = Network { name: Cow::Borrowed("XDai") };
+
+    /// Returns the built-in network with this name (case-insensitive), otherwise returns a
+    /// custom network with this name.
+    pub fn from_name(name: &str) -> Result<Self> {
+        let name = name.trim();
+        if !name.chars().any(|c| c.is_ascii_alphanumeric()) {
+            return Err(Error::InvalidNetworkName(name.to_string()));
         }
+        let built_in = [Network::CORE, Network::SOKOL, Network::XDAI]
+            .iter()
+            .find(|network| network.name.eq_ignore_ascii_case(name))
+            .cloned();
+        Ok(built_in.unwrap_or_else(|| Network { name: Cow::Owned(name.to_string()) }))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The identifier used to name the network's env-vars (e.g. `<ID>_RPC_ENDPOINT`), checkpoint
+    /// file, and notification ledger entries. The identifier is the network's name in uppercase
+    /// with each non-alphanumeric character replaced by an underscore, e.g. "my-testnet" becomes
+    /// "MY_TESTNET".
+    pub fn id(&self) -> String {
+        self.name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+            .collect()
+    }
+
+    /// The block explorer of a built-in network.
+    fn default_explorer_url(&self) -> Option<&'static str> {
+        match self.id().as_str() {
+            "CORE" => Some("https://blockscout.com/poa/core"),
+            "SOKOL" => Some("https://blockscout.com/poa/sokol"),
+            "XDAI" => Some("https://blockscout.com/poa/dai"),
+            _ => None,
+        }
+    }
+
+    fn is_xdai(&self) -> bool {
+        *self == Network::XDAI
+    }
+}
+
+impl PartialEq for Network {
+    fn eq(&self, other: &Network) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl Display for Network {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
     }
 }
 
 /// Note that the `Emission` contract is V2 only.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ContractType {
     Keys,
     Threshold,
@@ -73,9 +147,20 @@ impl ContractType {
             ContractType::Emission => "VotingToManageEmissionFunds.abi.json",
         }
     }
+
+    /// The name of the `ProxyStorage` function that returns this contract type's address.
+    pub fn proxy_storage_getter(&self) -> &str {
+        match self {
+            ContractType::Keys => "getVotingToChangeKeys",
+            ContractType::Threshold => "getVotingToChangeMinThreshold",
+            ContractType::Proxy => "getVotingToChangeProxy",
+            ContractType::Emission => "getVotingToManageEmissionFunds",
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ContractVersion {
     V1,
     V2,
@@ -124,8 +209,26 @@ impl PoaContract {
 
     pub fn read(
         contract_type: ContractType,
-        network: Network,
+        network: &Network,
+        version: ContractVersion,
+    ) -> Result<Self> {
+        PoaContract::read_with(contract_type, network, version, DEFAULT_ABI_DIR, None, false)
+    }
+
+    /// Reads the contract's address and ABI. If the contract is listed in the config file, the
+    /// config file's `address` and `abi` take precedence over the contract address env-var and the
+    /// ABI file in the network's ABI directory.
+    ///
+    /// When `discover` is set, the contract's address does not need to be configured; a contract
+    /// without a configured address has the zero address until it is discovered from the
+    /// network's `ProxyStorage` contract.
+    fn read_with(
+        contract_type: ContractType,
+        network: &Network,
         version: ContractVersion,
+        abi_dir: &str,
+        section: Option<&ContractSection>,
+        discover: bool,
     ) -> Result<Self> {
         // Exit quickly if we know that the contract does not exist.
         if contract_type.is_emission() && version.is_v1() {
@@ -135,18 +238,28 @@ impl PoaContract {
         let env_var = format!(
             "{}_CONTRACT_ADDRESS_{}_{:?}",
             contract_type.uppercase(),
-            network.uppercase(),
+            network.id(),
             version
         );
-        let contract_addr_str = env::var(&env_var).map_err(|_| Error::MissingEnvVar(env_var))?;
-        let contract_addr = Address::from_str(contract_addr_str.trim_left_matches("0x"))
-            .map_err(|_| Error::InvalidContractAddr(contract_addr_str.to_string()))?;
-
-        let abi_path = format!(
-            "abis/{}/{}",
-            version.lowercase(),
-            contract_type.abi_file_name()
-        );
+        let contract_addr_str = match section.and_then(|section| section.address.clone()) {
+            Some(contract_addr_str) => Some(contract_addr_str),
+            None => env::var(&env_var).ok(),
+        };
+        let contract_addr = match contract_addr_str {
+            Some(contract_addr_str) => parse_address(&contract_addr_str)?,
+            None if discover => Address::zero(),
+            None => return Err(Error::MissingEnvVar(env_var)),
+        };
+
+        let abi_path = match section.and_then(|section| section.abi.clone()) {
+            Some(abi_path) => abi_path,
+            None => format!(
+                "{}/{}/{}",
+                abi_dir,
+                version.lowercase(),
+                contract_type.abi_file_name()
+            ),
+        };
         let abi_file = File::open(&abi_path).map_err(|_| Error::MissingAbiFile(abi_path.clone()))?;
         let abi = Contract::load(&abi_file).map_err(|_| Error::InvalidAbi(abi_path))?;
 
@@ -160,6 +273,181 @@ impl PoaContract {
     pub fn function(&self, function: &str) -> Function {
         self.abi.function(function).unwrap().clone()
     }
+
+    /// Returns `true` if the contract's address has not been configured or discovered yet.
+    pub fn is_undiscovered(&self) -> bool {
+        self.addr == Address::zero()
+    }
+}
+
+/// A network's `ProxyStorage` contract, which stores the current address of each of the
+/// network's voting contracts (for a single contract version).
+#[derive(Clone)]
+pub struct ProxyStorage {
+    pub version: ContractVersion,
+    /// When `None`, the address is read from the `proxyStorage()` getter of one of the voting
+    /// contracts (with the same version) whose address is known.
+    pub addr: Option<Address>,
+    pub abi: Contract,
+}
+
+impl Debug for ProxyStorage {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("ProxyStorage")
+            .field("version", &self.version)
+            .field("addr", &self.addr)
+            .field("abi", &"<ethabi::Contract>")
+            .finish()
+    }
+}
+
+impl ProxyStorage {
+    /// Reads the `ProxyStorage` address for `version`, first from the config file then from the
+    /// `PROXY_STORAGE_CONTRACT_ADDRESS_<NETWORK>_<VERSION>` env-var. The address is optional.
+    fn read(
+        network: &Network,
+        version: ContractVersion,
+        abi_dir: &str,
+        section: Option<&ProxyStorageSection>,
+    ) -> Result<Self> {
+        let contract_addr_str = match section.and_then(|section| section.address(version)) {
+            Some(contract_addr_str) => Some(contract_addr_str),
+            None => {
+                let env_var = format!(
+                    "PROXY_STORAGE_CONTRACT_ADDRESS_{}_{:?}",
+                    network.id(),
+                    version
+                );
+                env::var(&env_var).ok()
+            }
+        };
+        let addr = match contract_addr_str {
+            Some(contract_addr_str) => Some(parse_address(&contract_addr_str)?),
+            None => None,
+        };
+        let abi_path = format!("{}/{}/ProxyStorage.abi.json", abi_dir, version.lowercase());
+        let abi_file = File::open(&abi_path).map_err(|_| Error::MissingAbiFile(abi_path.clone()))?;
+        let abi = Contract::load(&abi_file).map_err(|_| Error::InvalidAbi(abi_path))?;
+        Ok(ProxyStorage { version, addr, abi })
+    }
+
+    pub fn function(&self, function: &str) -> Function {
+        self.abi.function(function).unwrap().clone()
+    }
+}
+
+fn parse_address(s: &str) -> Result<Address> {
+    Address::from_str(s.trim().trim_left_matches("0x"))
+        .map_err(|_| Error::InvalidContractAddr(s.to_string()))
+}
+
+/// Determines whether or not notifications are generated for `Vote` events.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VoteNotifications {
+    Disabled,
+    /// One notification is generated for each vote cast (the `--votes` CLI flag).
+    PerVote,
+    /// One notification is generated for each ballot that received votes during a block-window
+    /// (the `--vote-digest` CLI flag).
+    Digest,
+}
+
+/// Determines when the notifications batched into an email digest are sent (the `--email-digest`
+/// CLI argument).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmailDigest {
+    /// One digest is sent after each block-window that generated notifications.
+    PerWindow,
+    /// One digest is sent per interval, the interval is checked after each block-window.
+    Interval(Duration),
+}
+
+impl EmailDigest {
+    fn parse(s: &str) -> Result<Self> {
+        if s == "window" {
+            Ok(EmailDigest::PerWindow)
+        } else {
+            parse_duration(s)
+                .map(EmailDigest::Interval)
+                .ok_or_else(|| Error::InvalidEmailDigest(s.to_string()))
+        }
+    }
+}
+
+/// The Handlebars templates used to render notification emails, templates that are not set use
+/// the built-in defaults (see `templates::EmailTemplates`).
+#[derive(Clone, Debug, Default)]
+pub struct EmailTemplateConfig {
+    pub subject: Option<String>,
+    /// Subject templates keyed by ballot type name (e.g. "AddKey").
+    pub subjects: HashMap<String, String>,
+    pub body_file: Option<String>,
+    /// Body template file paths keyed by ballot type name.
+    pub body_files: HashMap<String, String>,
+}
+
+/// The format that notifications are printed to stdout in (the `--output` CLI argument).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// Notifications are only logged (logs are written to stderr or to the log files).
+    Text,
+    /// Each notification is printed as a single-line JSON object (see `Notification::to_json`).
+    Json,
+}
+
+impl OutputFormat {
+    fn from_cli(cli: &Cli) -> Result<Self> {
+        match cli.output() {
+            None | Some("text") => Ok(OutputFormat::Text),
+            Some("json") => Ok(OutputFormat::Json),
+            Some(s) => Err(Error::InvalidOutputFormat(s.to_string())),
+        }
+    }
+}
+
+/// How the connection to the SMTP server is secured.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SmtpSecurity {
+    /// Plain-text SMTP, e.g. for an internal relay on port 25.
+    None,
+    /// STARTTLS is used if the server supports it.
+    Opportunistic,
+    /// STARTTLS is required.
+    Required,
+    /// The connection is wrapped in TLS from the start, e.g. on port 465.
+    Wrapper,
+}
+
+impl SmtpSecurity {
+    fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(SmtpSecurity::None),
+            "opportunistic" => Ok(SmtpSecurity::Opportunistic),
+            "required" => Ok(SmtpSecurity::Required),
+            "wrapper" => Ok(SmtpSecurity::Wrapper),
+            _ => Err(Error::InvalidSmtpSecurity(s.to_string())),
+        }
+    }
+}
+
+/// The SMTP authentication mechanism used when SMTP credentials are configured. For `Xoauth2`,
+/// the SMTP password is the OAuth2 access token.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SmtpAuthMechanism {
+    Plain,
+    Login,
+    Xoauth2,
+}
+
+impl SmtpAuthMechanism {
+    fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "plain" => Ok(SmtpAuthMechanism::Plain),
+            "login" => Ok(SmtpAuthMechanism::Login),
+            "xoauth2" => Ok(SmtpAuthMechanism::Xoauth2),
+            _ => Err(Error::InvalidSmtpAuthMechanism(s.to_string())),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -168,42 +456,417 @@ pub enum StartBlock {
     Latest,
     Number(u64),
     Tail(u64),
+    /// Contains the last block number processed by a previous run of `poagov` (read from the
+    /// network's checkpoint file).
+    Resume(u64),
+}
+
+/// An email address that notifications are sent to. A recipient can optionally be associated with
+/// a validator's voting key, in which case deadline reminders are only sent to the recipient for
+/// the ballots that the voting key has not yet voted on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recipient {
+    pub email: String,
+    pub voting_key: Option<Address>,
+    pub subscription: Subscription,
+}
+
+impl Recipient {
+    /// Parses a recipient from the `EMAIL_RECIPIENTS` env-var, each recipient is formatted as
+    /// either "<email>" or "<email>:<voting key>".
+    fn parse(s: &str) -> Result<Self> {
+        let mut split = s.trim().splitn(2, ':');
+        let email = split.next().unwrap().to_string();
+        let voting_key = match split.next() {
+            Some(voting_key_str) => {
+                let voting_key = Address::from_str(voting_key_str.trim_left_matches("0x"))
+                    .map_err(|_| Error::InvalidVotingKey(voting_key_str.to_string()))?;
+                Some(voting_key)
+            }
+            None => None,
+        };
+        Ok(Recipient {
+            email,
+            voting_key,
+            subscription: Subscription::default(),
+        })
+    }
+}
+
+/// Restricts the notifications that a recipient is sent to the given networks, contract types, and
+/// ballot types. An empty list matches every value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Subscription {
+    pub networks: Vec<Network>,
+    pub contract_types: Vec<ContractType>,
+    pub ballot_types: Vec<BallotType>,
 }
 
+impl Subscription {
+    fn from_section(section: &SubscriptionSection) -> Result<Self> {
+        let networks = section
+            .networks
+            .iter()
+            .map(|name| Network::from_name(name))
+            .collect::<Result<_>>()?;
+        Ok(Subscription {
+            networks,
+            contract_types: section.contract_types.clone(),
+            ballot_types: section.ballot_types.clone(),
+        })
+    }
+
+    pub fn matches(
+        &self,
+        network: &Network,
+        contract_type: ContractType,
+        ballot_type: BallotType,
+    ) -> bool {
+        (self.networks.is_empty() || self.networks.contains(network))
+            && (self.contract_types.is_empty() || self.contract_types.contains(&contract_type))
+            && (self.ballot_types.is_empty() || self.ballot_types.contains(&ballot_type))
+    }
+}
+
+/// Returns the config file's value for a setting if one was given, otherwise reads the setting
+/// from the env-var `env_var`.
+fn file_or_env(file_value: Option<String>, env_var: &str) -> Result<String> {
+    match file_value {
+        Some(value) => Ok(value),
+        None => env::var(env_var).map_err(|_| Error::MissingEnvVar(env_var.to_string())),
+    }
+}
+
+/// Returns the directory where `poagov` stores its state between runs. The `--state-dir` CLI
+/// argument takes precedence over the config file's `state_dir` setting.
+pub fn state_dir(cli: &Cli, file: Option<&ConfigFile>) -> String {
+    if let Some(state_dir) = cli.state_dir() {
+        return state_dir.to_string();
+    }
+    match file.and_then(|file| file.state_dir.clone()) {
+        Some(state_dir) => state_dir,
+        None => DEFAULT_STATE_DIR.to_string(),
+    }
+}
+
+/// Parses a positive duration formatted as an integer followed by one of the units: "d" (days),
+/// "h" (hours), or "m" (minutes), e.g. "24h". Returns `None` if `s` is not a valid duration.
+fn parse_duration(s: &str) -> Option<Duration> {
+    if s.len() < 2 {
+        return None;
+    }
+    let (n_str, unit) = s.split_at(s.len() - 1);
+    let n: i64 = n_str.parse().ok()?;
+    if n <= 0 {
+        return None;
+    }
+    match unit {
+        "d" => Some(Duration::days(n)),
+        "h" => Some(Duration::hours(n)),
+        "m" => Some(Duration::minutes(n)),
+        _ => None,
+    }
+}
+
+/// Parses a comma-separated list of reminder offsets (e.g. "24h,1h,30m") into `Duration`s (see
+/// `parse_duration`). The returned offsets are sorted from longest to shortest.
+fn parse_reminder_offsets(s: &str) -> Result<Vec<Duration>> {
+    let mut offsets = vec![];
+    for offset_str in s.split(',').map(|offset_str| offset_str.trim()) {
+        let offset = parse_duration(offset_str)
+            .ok_or_else(|| Error::InvalidReminderOffset(offset_str.to_string()))?;
+        offsets.push(offset);
+    }
+    offsets.sort_unstable_by(|offset1, offset2| offset2.cmp(offset1));
+    offsets.dedup();
+    Ok(offsets)
+}
+
+/// The configuration for a single network that `poagov` is monitoring. Each network is monitored
+/// using its own RPC client and block-window iterator.
 #[derive(Clone, Debug)]
-pub struct Config {
+pub struct NetworkConfig {
     pub network: Network,
     pub endpoint: String,
-    pub version: ContractVersion,
+    /// When set, `poagov` checks that the RPC endpoint is connected to the chain with this id.
+    pub chain_id: Option<u64>,
+    /// The `ProxyStorage` contracts that store the addresses of `contracts` (one per contract
+    /// version), only read when `--discover-contracts` is set.
+    pub proxy_storages: Vec<ProxyStorage>,
+    /// Set by the `--discover-contracts` CLI flag.
+    pub discover_contracts: bool,
     pub contracts: Vec<PoaContract>,
+    /// The V1 Proxy contract when it is not in `contracts`; its finalized ballots are only checked
+    /// for upgrades to the monitored contracts, they are not notified.
+    pub proxy_contracts: Vec<PoaContract>,
     pub start_block: StartBlock,
     pub block_time: u64,
+    /// The Slack or Mattermost incoming-webhook URLs that this network's notifications are posted
+    /// to (when the `--chat` CLI flag is set).
+    pub chat_webhooks: Vec<String>,
+    /// The block explorer that addresses and block numbers are linked to in HTML emails.
+    pub explorer_url: Option<String>,
+    /// The governance DApp's ballot page that ballots are linked to in HTML emails,
+    /// "{ballot_id}" and "{contract}" are replaced by the ballot's id and contract address.
+    pub ballot_url: Option<String>,
+}
+
+impl NetworkConfig {
+    fn new(
+        cli: &Cli,
+        network: Network,
+        versions: &[ContractVersion],
+        state_dir: &str,
+        section: Option<&NetworkSection>,
+    ) -> Result<Self> {
+        let endpoint = match section.and_then(|section| section.endpoint.clone()) {
+            Some(endpoint) => endpoint,
+            None => {
+                let endpoint_env_var = format!("{}_RPC_ENDPOINT", network.id());
+                env::var(&endpoint_env_var).map_err(|_| Error::MissingEnvVar(endpoint_env_var))?
+            }
+        };
+
+        let chain_id = section.and_then(|section| section.chain_id);
+        let abi_dir = section
+            .and_then(|section| section.abi_dir.clone())
+            .unwrap_or_else(|| DEFAULT_ABI_DIR.to_string());
+
+        let mut contract_types = vec![];
+        if cli.keys() {
+            contract_types.push(ContractType::Keys);
+        }
+        if cli.threshold() {
+            contract_types.push(ContractType::Threshold);
+        }
+        if cli.proxy() {
+            contract_types.push(ContractType::Proxy);
+        }
+        if cli.emission() {
+            contract_types.push(ContractType::Emission);
+        }
+
+        let mut contracts = vec![];
+        if contract_types.is_empty() {
+            // If no contract CLI arguments were given, we monitor each of the network's contracts
+            // listed in the config file (only those with the versions given via `--v1`/`--v2`).
+            let contract_sections = section.iter().flat_map(|section| section.contracts.iter());
+            for contract_section in contract_sections {
+                let version = contract_section.version;
+                if cli.no_versions_specified() || versions.contains(&version) {
+                    let contract = PoaContract::read_with(
+                        contract_section.kind,
+                        &network,
+                        version,
+                        &abi_dir,
+                        Some(contract_section),
+                        cli.discover_contracts(),
+                    )?;
+                    contracts.push(contract);
+                }
+            }
+        } else {
+            // When both `--v1` and `--v2` are set, we skip the V1 contracts that were never
+            // deployed (the V1 Emission Funds contract and all V1 contracts on the xDai chain).
+            for version in versions.iter().cloned() {
+                if version.is_v1() && network.is_xdai() {
+                    continue;
+                }
+                for contract_type in contract_types.iter().cloned() {
+                    if contract_type.is_emission() && version.is_v1() {
+                        continue;
+                    }
+                    let contract_section =
+                        section.and_then(|section| section.contract(contract_type, version));
+                    let contract = PoaContract::read_with(
+                        contract_type,
+                        &network,
+                        version,
+                        &abi_dir,
+                        contract_section,
+                        cli.discover_contracts(),
+                    )?;
+                    contracts.push(contract);
+                }
+            }
+        }
+        if contracts.is_empty() {
+            return Err(Error::NoContractsToMonitor(network.to_string()));
+        }
+
+        // An accepted V1 proxy ballot replaces one of the V1 voting contracts, so the network's V1
+        // Proxy contract is always checked for upgrades, even when its ballots are not monitored.
+        // V2 voting contracts are upgraded in place, their addresses never change.
+        let mut proxy_contracts = vec![];
+        let monitors_v1 = contracts.iter().any(|contract| contract.version.is_v1());
+        let monitors_v1_proxy = contracts
+            .iter()
+            .any(|contract| contract.version.is_v1() && contract.kind == ContractType::Proxy);
+        if monitors_v1 && !monitors_v1_proxy {
+            let version = ContractVersion::V1;
+            let contract_section =
+                section.and_then(|section| section.contract(ContractType::Proxy, version));
+            let proxy_contract = PoaContract::read_with(
+                ContractType::Proxy,
+                &network,
+                version,
+                &abi_dir,
+                contract_section,
+                cli.discover_contracts(),
+            )?;
+            proxy_contracts.push(proxy_contract);
+        }
+
+        // `ProxyStorage` is only used to discover contract addresses (`--discover-contracts`), so
+        // its ABI is only required when discovery is enabled.
+        let mut proxy_storages = vec![];
+        if cli.discover_contracts() {
+            let proxy_storage_section =
+                section.and_then(|section| section.proxy_storage.as_ref());
+            for version in [ContractVersion::V1, ContractVersion::V2].iter().cloned() {
+                if contracts.iter().any(|contract| contract.version == version) {
+                    let proxy_storage =
+                        ProxyStorage::read(&network, version, &abi_dir, proxy_storage_section)?;
+                    proxy_storages.push(proxy_storage);
+                }
+            }
+        }
+        let discover_contracts = cli.discover_contracts();
+
+        let start_block = if cli.earliest() {
+            StartBlock::Earliest
+        } else if cli.latest() {
+            StartBlock::Latest
+        } else if let Some(start_block_str) = cli.start_block() {
+            match start_block_str.parse::<u64>() {
+                Ok(block_number) => StartBlock::Number(block_number),
+                _ => return Err(Error::InvalidStartBlock(start_block_str.to_string())),
+            }
+        } else if let Some(tail_str) = cli.tail() {
+            match tail_str.parse::<u64>() {
+                Ok(tail) => StartBlock::Tail(tail),
+                _ => return Err(Error::InvalidTail(tail_str.to_string())),
+            }
+        } else if cli.resume() {
+            let checkpoint = Checkpoint::new(state_dir, &network);
+            match checkpoint.read()? {
+                Some(last_processed_block) => StartBlock::Resume(last_processed_block),
+                None => return Err(Error::MissingCheckpoint(checkpoint.path())),
+            }
+        } else {
+            // TODO: use `DEFAULT_START_BLOCK`?
+            unreachable!();
+        };
+
+        // The `--block-time` CLI argument applies to every network, otherwise each network's
+        // block time can be set in the config file or using the `<NETWORK>_BLOCK_TIME` env-var.
+        let block_time_env_var = format!("{}_BLOCK_TIME", network.id());
+        let block_time = if let Some(n_secs_str) = cli.block_time() {
+            n_secs_str.parse().map_err(|_| Error::InvalidBlockTime(n_secs_str.to_string()))?
+        } else if let Some(block_time) = section.and_then(|section| section.block_time) {
+            block_time
+        } else if let Ok(n_secs_str) = env::var(&block_time_env_var) {
+            n_secs_str.parse().map_err(|_| Error::InvalidBlockTime(n_secs_str.to_string()))?
+        } else {
+            DEFAULT_BLOCK_TIME_SECS
+        };
+
+        let chat_webhooks_env_var = format!("{}_CHAT_WEBHOOKS", network.id());
+        let chat_webhooks = match section.and_then(|section| section.chat_webhooks.clone()) {
+            Some(chat_webhooks) => chat_webhooks,
+            None => match env::var(&chat_webhooks_env_var) {
+                Ok(urls) => urls
+                    .split(',')
+                    .map(|url| url.trim().to_string())
+                    .filter(|url| !url.is_empty())
+                    .collect(),
+                Err(_) => vec![],
+            },
+        };
+
+        let explorer_url = match section.and_then(|section| section.explorer_url.clone()) {
+            Some(explorer_url) => Some(explorer_url),
+            None => env::var(format!("{}_EXPLORER_URL", network.id()))
+                .ok()
+                .or_else(|| network.default_explorer_url().map(String::from)),
+        };
+        let ballot_url = match section.and_then(|section| section.ballot_url.clone()) {
+            Some(ballot_url) => Some(ballot_url),
+            None => env::var(format!("{}_BALLOT_URL", network.id())).ok(),
+        };
+
+        Ok(NetworkConfig {
+            network,
+            endpoint,
+            chain_id,
+            proxy_storages,
+            discover_contracts,
+            contracts,
+            proxy_contracts,
+            start_block,
+            block_time,
+            chat_webhooks,
+            explorer_url,
+            ballot_url,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub networks: Vec<NetworkConfig>,
+    pub vote_notifications: VoteNotifications,
+    pub reminder_offsets: Vec<Duration>,
+    pub finalize_alerts: bool,
     pub email_notifications: bool,
-    pub email_recipients: Vec<String>,
+    pub chat_notifications: bool,
+    pub email_recipients: Vec<Recipient>,
     pub smtp_host_domain: Option<String>,
     pub smtp_port: Option<u16>,
     pub smtp_username: Option<String>,
     pub smtp_password: Option<String>,
+    pub smtp_security: SmtpSecurity,
+    pub smtp_auth_mechanism: SmtpAuthMechanism,
     pub outgoing_email_addr: Option<String>,
+    pub email_max_attempts: u32,
+    pub email_templates: EmailTemplateConfig,
+    pub email_digest: Option<EmailDigest>,
+    pub telegram_notifications: bool,
+    pub telegram_bot_token: Option<String>,
+    pub telegram_chat_ids: Vec<String>,
+    pub telegram_api_url: Option<String>,
+    pub webhook_notifications: bool,
+    pub webhook_urls: Vec<String>,
+    pub webhook_secret: Option<String>,
+    pub webhook_max_retries: u32,
+    pub watch_list: Vec<WatchedAddress>,
     pub notification_limit: Option<usize>,
+    pub output: OutputFormat,
     pub log_emails: bool,
     pub log_to_file: bool,
+    pub state_dir: String,
+    pub resend: bool,
 }
 
 impl Config {
     pub fn new(cli: &Cli) -> Result<Self> {
-        if !cli.one_network_specified() {
-            return Err(Error::MustSpecifyOneCliArgument("--core, --sokol".to_string()));
-        }
-        if cli.multiple_versions_specified() {
-            return Err(Error::MustSpecifyZeroOrOneCliArguments("--v1, --v2".to_string()));
+        let file = ConfigFile::from_cli(cli)?;
+
+        // When a config file is given, the networks and contracts to monitor can be read from the
+        // config file instead of from the CLI arguments.
+        if cli.no_networks_specified() && file.is_none() {
+            return Err(Error::MustSpecifyAtLeastOneCliArgument(
+                "--core, --sokol, --xdai, --network".to_string(),
+            ));
         }
-        if cli.no_contracts_specified() {
+        if cli.no_contracts_specified() && file.is_none() {
             return Err(Error::MustSpecifyAtLeastOneCliArgument(
                 "--keys, --threshold, --proxy, --emission".to_string().to_string(),
             ));
         }
-        if cli.v1() {
+        // If both `--v1` and `--v2` are set, the V2-only contracts and networks are monitored using
+        // the V2 contracts alone.
+        if cli.v1() && !cli.v2() {
             if cli.xdai() {
                 return Err(Error::V1ContractsWereNotDeployedToXDaiChain);
             }
@@ -211,95 +874,128 @@ impl Config {
                 return Err(Error::EmissionFundsV1ContractDoesNotExist);
             }
         }
+        if cli.votes() && cli.vote_digest() {
+            return Err(Error::MustSpecifyZeroOrOneCliArguments(
+                "--votes, --vote-digest".to_string(),
+            ));
+        }
         if !cli.one_start_block_was_specified() {
             return Err(Error::MustSpecifyOneCliArgument(
-                "--earliest, --latest, --start-block, --tail".to_string()
+                "--earliest, --latest, --start-block, --tail, --resume".to_string()
             ));
         }
 
-        let network = if cli.core() {
-            Network::Core
-        } else if cli.sokol() {
-            Network::Sokol
-        } else {
-            Network::XDai
-        };
-
         // We default to `V2` if no contract version CLI argument was supplied.
-        let version = if cli.v1() {
-            ContractVersion::V1
-        } else {
-            ContractVersion::V2
-        };
+        let mut versions = vec![];
+        if cli.v1() {
+            versions.push(ContractVersion::V1);
+        }
+        if cli.v2() || !cli.v1() {
+            versions.push(ContractVersion::V2);
+        }
 
-        let endpoint_env_var = format!("{}_RPC_ENDPOINT", network.uppercase());
-        let endpoint = env::var(&endpoint_env_var)
-            .map_err(|_| Error::MissingEnvVar(endpoint_env_var))?;
+        let state_dir = state_dir(cli, file.as_ref());
 
-        let mut contracts = vec![];
-        if cli.keys() {
-            let keys_contract = PoaContract::read(ContractType::Keys, network, version)?;
-            contracts.push(keys_contract);
+        // If no network CLI arguments were given, we monitor each network listed in the config
+        // file.
+        let mut network_names: Vec<Network> = vec![];
+        if cli.no_networks_specified() {
+            for section in file.iter().flat_map(|file| file.networks.iter()) {
+                network_names.push(Network::from_name(&section.name)?);
+            }
+        } else {
+            if cli.core() {
+                network_names.push(Network::CORE);
+            }
+            if cli.sokol() {
+                network_names.push(Network::SOKOL);
+            }
+            if cli.xdai() {
+                network_names.push(Network::XDAI);
+            }
+            for name in cli.networks() {
+                network_names.push(Network::from_name(name)?);
+            }
         }
-        if cli.threshold() {
-            let threshold_contract = PoaContract::read(ContractType::Threshold, network, version)?;
-            contracts.push(threshold_contract);
+        // A network may have been given more than once (e.g. `--sokol --network=sokol`).
+        let network_names = network_names.into_iter().fold(vec![], |mut unique, network| {
+            if !unique.contains(&network) {
+                unique.push(network);
+            }
+            unique
+        });
+        if network_names.is_empty() {
+            return Err(Error::MustSpecifyAtLeastOneCliArgument(
+                "--core, --sokol, --xdai, --network".to_string(),
+            ));
         }
-        if cli.proxy() {
-            let proxy_contract = PoaContract::read(ContractType::Proxy, network, version)?;
-            contracts.push(proxy_contract);
+        // Block numbers are not comparable across networks.
+        if cli.start_block().is_some() && network_names.len() > 1 {
+            return Err(Error::StartBlockRequiresOneNetwork);
         }
-        if cli.emission() {
-            let emission_funds = PoaContract::read(ContractType::Emission, network, version)?;
-            contracts.push(emission_funds);
+
+        let mut networks = vec![];
+        for network in network_names {
+            let section = file.as_ref().and_then(|file| file.network(&network));
+            let network_config = NetworkConfig::new(cli, network, &versions, &state_dir, section)?;
+            networks.push(network_config);
         }
 
-        let start_block = if cli.earliest() {
-            StartBlock::Earliest
-        } else if cli.latest() {
-            StartBlock::Latest
-        } else if let Some(start_block_str) = cli.start_block() {
-            match start_block_str.parse::<u64>() {
-                Ok(block_number) => StartBlock::Number(block_number),
-                _ => return Err(Error::InvalidStartBlock(start_block_str.to_string())),
-            }
-        } else if let Some(tail_str) = cli.tail() {
-            match tail_str.parse::<u64>() {
-                Ok(tail) => StartBlock::Tail(tail),
-                _ => return Err(Error::InvalidTail(tail_str.to_string())),
-            }
+        let vote_notifications = if cli.votes() {
+            VoteNotifications::PerVote
+        } else if cli.vote_digest() {
+            VoteNotifications::Digest
         } else {
-            // TODO: use `DEFAULT_START_BLOCK`?
-            unreachable!();
+            VoteNotifications::Disabled
         };
 
-        let block_time = if let Some(n_secs_str) = cli.block_time() {
-            n_secs_str.parse().map_err(|_| Error::InvalidBlockTime(n_secs_str.to_string()))?
-        } else {
-            DEFAULT_BLOCK_TIME_SECS
+        let reminder_offsets = match cli.reminders() {
+            Some(s) => parse_reminder_offsets(s)?,
+            None => vec![],
         };
+        let finalize_alerts = cli.finalize_alerts();
 
         let email_notifications = cli.email();
+        let chat_notifications = cli.chat();
+
+        // Each email setting is read from the config file's `[email]` table if it is set there,
+        // otherwise the setting is read from its env-var.
+        let email_section = file
+            .as_ref()
+            .and_then(|file| file.email.clone())
+            .unwrap_or_default();
 
         // TODO: should the recipient email addresses be validated here? For now, we just allow
         // email sending to fail, which will then get logged to the user.
-        let email_recipients: Vec<String> = env::var("EMAIL_RECIPIENTS")
-            .map_err(|_| Error::MissingEnvVar("EMAIL_RECIPIENTS".to_string()))?
-            .split(',')
-            .map(|recipient_email_address| recipient_email_address.to_string())
-            .collect();
+        let mut email_recipients: Vec<Recipient> = match email_section.recipients {
+            Some(ref recipients) => recipients
+                .iter()
+                .map(|recipient| Recipient::parse(recipient))
+                .collect::<Result<_>>()?,
+            None => env::var("EMAIL_RECIPIENTS")
+                .map_err(|_| Error::MissingEnvVar("EMAIL_RECIPIENTS".to_string()))?
+                .split(',')
+                .map(Recipient::parse)
+                .collect::<Result<_>>()?,
+        };
+        for (email, section) in email_section.subscriptions.iter() {
+            let recipient = email_recipients
+                .iter_mut()
+                .find(|recipient| recipient.email == *email)
+                .ok_or_else(|| Error::UnknownSubscriptionRecipient(email.to_string()))?;
+            recipient.subscription = Subscription::from_section(section)?;
+        }
 
         let smtp_host_domain = if email_notifications {
-            match env::var("SMTP_HOST_DOMAIN") {
-                Ok(host) => Some(host),
-                _ => return Err(Error::MissingEnvVar("SMTP_HOST_DOMAIN".to_string())),
-            }
+            Some(file_or_env(email_section.smtp_host_domain, "SMTP_HOST_DOMAIN")?)
         } else {
             None
         };
 
         let smtp_port = if email_notifications {
-            if let Ok(s) = env::var("SMTP_PORT") {
+            if let Some(port) = email_section.smtp_port {
+                Some(port)
+            } else if let Ok(s) = env::var("SMTP_PORT") {
                 let port = s.parse().map_err(|_| Error::InvalidSmtpPort(s.to_string()))?;
                 Some(port)
             } else {
@@ -309,33 +1005,165 @@ impl Config {
             None
         };
 
-        let smtp_username = if email_notifications {
-            match env::var("SMTP_USERNAME") {
-                Ok(username) => Some(username),
-                _ => return Err(Error::MissingEnvVar("SMTP_USERNAME".into())),
+        let smtp_security = if email_notifications {
+            match file_or_env(email_section.smtp_security, "SMTP_SECURITY") {
+                Ok(s) => SmtpSecurity::parse(&s)?,
+                Err(_) => SmtpSecurity::Required,
             }
+        } else {
+            SmtpSecurity::Required
+        };
+
+        let smtp_auth_mechanism = if email_notifications {
+            match file_or_env(email_section.smtp_auth_mechanism, "SMTP_AUTH_MECHANISM") {
+                Ok(s) => SmtpAuthMechanism::parse(&s)?,
+                Err(_) => SmtpAuthMechanism::Plain,
+            }
+        } else {
+            SmtpAuthMechanism::Plain
+        };
+
+        // SMTP credentials are optional (e.g. for an unauthenticated relay), but the username and
+        // password must be given together.
+        let (smtp_username, smtp_password) = if email_notifications {
+            let username = file_or_env(email_section.smtp_username, "SMTP_USERNAME")
+                .ok()
+                .filter(|s| !s.is_empty());
+            let password = file_or_env(email_section.smtp_password, "SMTP_PASSWORD")
+                .ok()
+                .filter(|s| !s.is_empty());
+            match (username, password) {
+                (Some(username), Some(password)) => (Some(username), Some(password)),
+                (None, None) => (None, None),
+                (Some(_), None) => return Err(Error::MissingEnvVar("SMTP_PASSWORD".into())),
+                (None, Some(_)) => return Err(Error::MissingEnvVar("SMTP_USERNAME".into())),
+            }
+        } else {
+            (None, None)
+        };
+
+        let outgoing_email_addr = if email_notifications {
+            Some(file_or_env(email_section.outgoing_email_address, "OUTGOING_EMAIL_ADDRESS")?)
         } else {
             None
         };
 
-        let smtp_password = if email_notifications {
-            match env::var("SMTP_PASSWORD") {
-                Ok(password) => Some(password),
-                _ => return Err(Error::MissingEnvVar("SMTP_PASSWORD".to_string())),
+        let email_max_attempts = if let Some(max_attempts) = email_section.max_attempts {
+            max_attempts
+        } else if let Ok(s) = env::var("EMAIL_MAX_ATTEMPTS") {
+            s.parse().map_err(|_| Error::InvalidEmailMaxAttempts(s.to_string()))?
+        } else {
+            DEFAULT_EMAIL_MAX_ATTEMPTS
+        };
+        if email_max_attempts == 0 {
+            return Err(Error::InvalidEmailMaxAttempts("0".to_string()));
+        }
+
+        // Templates are optional, the subject template and body template file can be set in the
+        // `[email.templates]` table or by their env-vars. Per-ballot-type templates can only be set
+        // in the config file.
+        let email_templates = {
+            let templates_section = email_section.templates.clone().unwrap_or_default();
+            EmailTemplateConfig {
+                subject: file_or_env(templates_section.subject, "EMAIL_SUBJECT_TEMPLATE").ok(),
+                subjects: templates_section.subjects,
+                body_file: file_or_env(templates_section.body, "EMAIL_BODY_TEMPLATE_FILE").ok(),
+                body_files: templates_section.bodies,
             }
+        };
+
+        let email_digest = match cli.email_digest() {
+            Some(s) => Some(EmailDigest::parse(s)?),
+            None => None,
+        };
+
+        // Each Telegram setting is read from the config file's `[telegram]` table if it is set
+        // there, otherwise the setting is read from its env-var.
+        let telegram_notifications = cli.telegram();
+        let telegram_section = file
+            .as_ref()
+            .and_then(|file| file.telegram.clone())
+            .unwrap_or_default();
+
+        let telegram_bot_token = if telegram_notifications {
+            Some(file_or_env(telegram_section.bot_token, "TELEGRAM_BOT_TOKEN")?)
         } else {
             None
         };
 
-        let outgoing_email_addr = if email_notifications {
-            match env::var("OUTGOING_EMAIL_ADDRESS") {
-                Ok(outgoing_email_addr) => Some(outgoing_email_addr),
-                _ => return Err(Error::MissingEnvVar("OUTGOING_EMAIL_ADDRESS".to_string())),
+        let telegram_chat_ids = if telegram_notifications {
+            match telegram_section.chat_ids {
+                Some(chat_ids) => chat_ids,
+                None => file_or_env(None, "TELEGRAM_CHAT_IDS")?
+                    .split(',')
+                    .map(|chat_id| chat_id.trim().to_string())
+                    .filter(|chat_id| !chat_id.is_empty())
+                    .collect(),
             }
+        } else {
+            vec![]
+        };
+
+        let telegram_api_url = if telegram_notifications {
+            let api_url = file_or_env(telegram_section.api_url, "TELEGRAM_API_URL")
+                .unwrap_or_else(|_| DEFAULT_TELEGRAM_API_URL.to_string());
+            Some(api_url)
         } else {
             None
         };
 
+        // Each webhook setting is read from the config file's `[webhook]` table if it is set
+        // there, otherwise the setting is read from its env-var.
+        let webhook_notifications = cli.webhook();
+        let webhook_section = file
+            .as_ref()
+            .and_then(|file| file.webhook.clone())
+            .unwrap_or_default();
+
+        let webhook_urls = if webhook_notifications {
+            match webhook_section.urls {
+                Some(urls) => urls,
+                None => file_or_env(None, "WEBHOOK_URLS")?
+                    .split(',')
+                    .map(|url| url.trim().to_string())
+                    .filter(|url| !url.is_empty())
+                    .collect(),
+            }
+        } else {
+            vec![]
+        };
+
+        let webhook_secret = if webhook_notifications {
+            Some(file_or_env(webhook_section.secret, "WEBHOOK_SECRET")?)
+        } else {
+            None
+        };
+
+        let webhook_max_retries = if let Some(max_retries) = webhook_section.max_retries {
+            max_retries
+        } else if let Ok(s) = env::var("WEBHOOK_MAX_RETRIES") {
+            s.parse().map_err(|_| Error::InvalidWebhookMaxRetries(s.to_string()))?
+        } else {
+            DEFAULT_WEBHOOK_MAX_RETRIES
+        };
+
+        // The watch-list is optional, ballots containing a watched address are escalated (see
+        // `watch::find_matches`).
+        let watch_list_section = file.as_ref().and_then(|file| file.watch_list.clone());
+        let watch_list_strs: Vec<String> = match watch_list_section {
+            Some(watch_list) => watch_list,
+            None => env::var("WATCH_LIST")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.to_string())
+                .filter(|s| !s.trim().is_empty())
+                .collect(),
+        };
+        let watch_list = watch_list_strs
+            .iter()
+            .map(|s| WatchedAddress::parse(s))
+            .collect::<Result<_>>()?;
+
         let notification_limit = if let Some(s) = cli.notification_limit() {
             let limit = s
                 .parse()
@@ -345,38 +1173,98 @@ impl Config {
             None
         };
 
+        let output = OutputFormat::from_cli(cli)?;
         let log_emails = cli.log_emails();
         let log_to_file = cli.log_to_file();
+        let resend = cli.resend();
 
         Ok(Config {
-            network,
-            endpoint,
-            version,
-            contracts,
-            start_block,
-            block_time,
+            networks,
+            vote_notifications,
+            reminder_offsets,
+            finalize_alerts,
             email_notifications,
+            chat_notifications,
             email_recipients,
             smtp_host_domain,
             smtp_port,
             smtp_username,
             smtp_password,
+            smtp_security,
+            smtp_auth_mechanism,
             outgoing_email_addr,
+            email_max_attempts,
+            email_templates,
+            email_digest,
+            telegram_notifications,
+            telegram_bot_token,
+            telegram_chat_ids,
+            telegram_api_url,
+            webhook_notifications,
+            webhook_urls,
+            webhook_secret,
+            webhook_max_retries,
+            watch_list,
             notification_limit,
+            output,
             log_emails,
             log_to_file,
+            state_dir,
+            resend,
         })
     }
+
+    /// Assembles the sinks that notifications are sent through: the log, stdout (when
+    /// `--output json` is set), and each enabled notification channel.
+    pub fn sinks<'a>(&'a self, logger: &Arc<Mutex<Logger>>) -> Result<Vec<Box<dyn Sink + 'a>>> {
+        let mut sinks: Vec<Box<dyn Sink + 'a>> = vec![];
+        let log_sink = LogSink::new(self.log_emails, self.watch_list.clone(), logger.clone());
+        sinks.push(Box::new(log_sink));
+        if self.output == OutputFormat::Json {
+            sinks.push(Box::new(JsonOutputSink::new(self.watch_list.clone())));
+        }
+        if self.email_notifications {
+            sinks.push(Box::new(EmailSink::new(self, logger.clone())?));
+        }
+        if self.chat_notifications {
+            sinks.push(Box::new(ChatSink::new(self.watch_list.clone(), logger.clone())));
+        }
+        if self.telegram_notifications {
+            let api_url = self.telegram_api_url.clone().unwrap();
+            let bot_token = self.telegram_bot_token.clone().unwrap();
+            let bot = TelegramBot::new(&api_url, &bot_token, self.watch_list.clone());
+            let chat_ids = self.telegram_chat_ids.clone();
+            sinks.push(Box::new(TelegramSink::new(bot, chat_ids, logger.clone())));
+        }
+        if self.webhook_notifications {
+            let secret = self.webhook_secret.clone().unwrap();
+            let webhook =
+                JsonWebhook::new(&secret, self.webhook_max_retries, self.watch_list.clone());
+            let urls = self.webhook_urls.clone();
+            sinks.push(Box::new(WebhookSink::new(webhook, urls, logger.clone())));
+        }
+        Ok(sinks)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::str::FromStr;
 
-    use super::PoaContract;
+    use chrono::Duration;
+    use ethabi::Address;
+
+    use super::{
+        parse_reminder_offsets, Config, ContractType, ContractVersion, EmailDigest, Network,
+        PoaContract, Recipient, SmtpAuthMechanism, SmtpSecurity, Subscription,
+    };
+    use crate::cli::parse_cli_from;
+    use crate::error::Error;
+    use crate::response::common::BallotType;
     use crate::tests::{
         setup, CORE_NETWORK, SOKOL_NETWORK, V1_CONTRACT_TYPES, V1_VERSION, V2_CONTRACT_TYPES,
-        V2_VERSION,
+        V2_VERSION, XDAI_NETWORK,
     };
 
     #[test]
@@ -424,18 +1312,156 @@ mod tests {
 
         // Load all of the V1 contracts.
         for contract_type in V1_CONTRACT_TYPES.iter() {
-            let res = PoaContract::read(*contract_type, SOKOL_NETWORK, V1_VERSION);
+            let res = PoaContract::read(*contract_type, &SOKOL_NETWORK, V1_VERSION);
             assert!(res.is_ok());
-            let res = PoaContract::read(*contract_type, CORE_NETWORK, V1_VERSION);
+            let res = PoaContract::read(*contract_type, &CORE_NETWORK, V1_VERSION);
             assert!(res.is_ok());
         }
 
         // Load all of the V2 contracts.
         for contract_type in V2_CONTRACT_TYPES.iter() {
-            let res = PoaContract::read(*contract_type, SOKOL_NETWORK, V2_VERSION);
+            let res = PoaContract::read(*contract_type, &SOKOL_NETWORK, V2_VERSION);
             assert!(res.is_ok());
-            let res = PoaContract::read(*contract_type, CORE_NETWORK, V2_VERSION);
+            let res = PoaContract::read(*contract_type, &CORE_NETWORK, V2_VERSION);
             assert!(res.is_ok());
         }
     }
+
+    #[test]
+    fn test_parse_reminder_offsets() {
+        let offsets = parse_reminder_offsets("1h, 2d,30m,24h").unwrap();
+        assert_eq!(
+            offsets,
+            vec![Duration::days(2), Duration::hours(24), Duration::hours(1), Duration::minutes(30)],
+        );
+        assert!(parse_reminder_offsets("1h,").is_err());
+        assert!(parse_reminder_offsets("0h").is_err());
+        assert!(parse_reminder_offsets("1w").is_err());
+
+        assert_eq!(EmailDigest::parse("window").unwrap(), EmailDigest::PerWindow);
+        assert_eq!(EmailDigest::parse("6h").unwrap(), EmailDigest::Interval(Duration::hours(6)));
+        assert!(EmailDigest::parse("daily").is_err());
+    }
+
+    #[test]
+    fn test_parse_recipient() {
+        let recipient = Recipient::parse("alice@poa.network").unwrap();
+        assert_eq!(recipient.email, "alice@poa.network");
+        assert!(recipient.voting_key.is_none());
+
+        let recipient_str = " bob@poa.network:0x82e4e61e7f5139ff0a4157a5bc687ef42294c248";
+        let recipient = Recipient::parse(recipient_str).unwrap();
+        let voting_key = Address::from_str("82e4e61e7f5139ff0a4157a5bc687ef42294c248").unwrap();
+        assert_eq!(recipient.email, "bob@poa.network");
+        assert_eq!(recipient.voting_key, Some(voting_key));
+
+        assert!(Recipient::parse("carol@poa.network:0xnotanaddress").is_err());
+    }
+
+    #[test]
+    fn test_subscription_matches() {
+        assert!(Subscription::default().matches(
+            &SOKOL_NETWORK,
+            ContractType::Emission,
+            BallotType::Emission,
+        ));
+
+        let treasury = Subscription {
+            ballot_types: vec![BallotType::Emission],
+            ..Default::default()
+        };
+        assert!(treasury.matches(&CORE_NETWORK, ContractType::Emission, BallotType::Emission));
+        assert!(!treasury.matches(&CORE_NETWORK, ContractType::Keys, BallotType::AddKey));
+
+        let keys_team = Subscription {
+            networks: vec![Network::from_name("core").unwrap()],
+            ballot_types: vec![BallotType::AddKey, BallotType::RemoveKey, BallotType::SwapKey],
+            ..Default::default()
+        };
+        assert!(keys_team.matches(&CORE_NETWORK, ContractType::Keys, BallotType::SwapKey));
+        assert!(!keys_team.matches(&SOKOL_NETWORK, ContractType::Keys, BallotType::SwapKey));
+        assert!(!keys_team.matches(&CORE_NETWORK, ContractType::Keys, BallotType::InvalidKey));
+    }
+
+    #[test]
+    fn test_parse_smtp_options() {
+        assert_eq!(SmtpSecurity::parse("none").unwrap(), SmtpSecurity::None);
+        assert!(SmtpSecurity::parse("starttls").is_err());
+        assert_eq!(SmtpSecurity::parse("Wrapper").unwrap(), SmtpSecurity::Wrapper);
+        assert_eq!(SmtpAuthMechanism::parse("login").unwrap(), SmtpAuthMechanism::Login);
+        assert_eq!(SmtpAuthMechanism::parse("XOAUTH2").unwrap(), SmtpAuthMechanism::Xoauth2);
+        assert!(SmtpAuthMechanism::parse("cram-md5").is_err());
+    }
+
+    #[test]
+    fn test_network_from_name() {
+        let sokol = Network::from_name("sokol").unwrap();
+        assert_eq!(sokol, Network::SOKOL);
+        assert_eq!(sokol.to_string(), "Sokol");
+        assert_eq!(Network::from_name("xdai").unwrap().id(), "XDAI");
+
+        let testnet = Network::from_name(" my-testnet ").unwrap();
+        assert_eq!(testnet.name(), "my-testnet");
+        assert_eq!(testnet.id(), "MY_TESTNET");
+        assert_eq!(testnet, Network::from_name("MY_TESTNET").unwrap());
+
+        assert!(Network::from_name("").is_err());
+        assert!(Network::from_name("--").is_err());
+    }
+
+    #[test]
+    fn test_config_for_multiple_networks() {
+        setup();
+
+        let cli = parse_cli_from(vec!["poagov", "--core", "--sokol", "-t", "--latest"]);
+        let config = Config::new(&cli).unwrap();
+        assert_eq!(config.networks.len(), 2);
+        assert_eq!(config.networks[0].network, CORE_NETWORK);
+        assert_eq!(config.networks[1].network, SOKOL_NETWORK);
+
+        // A network given more than once is only monitored once.
+        let cli = parse_cli_from(vec!["poagov", "--sokol", "--network=sokol", "-t", "--latest"]);
+        let config = Config::new(&cli).unwrap();
+        assert_eq!(config.networks.len(), 1);
+        assert_eq!(config.networks[0].network, SOKOL_NETWORK);
+
+        let cli = parse_cli_from(vec!["poagov", "--core", "--sokol", "-t", "--start=100"]);
+        match Config::new(&cli).err() {
+            Some(Error::StartBlockRequiresOneNetwork) => {}
+            e => panic!("expected `StartBlockRequiresOneNetwork`, found: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_config_for_v1_and_v2_contracts() {
+        setup();
+
+        let args = vec!["poagov", "--sokol", "--xdai", "--v1", "--v2", "-t", "-e", "--latest"];
+        let config = Config::new(&parse_cli_from(args)).unwrap();
+        let contracts = |i: usize| -> Vec<(ContractType, ContractVersion)> {
+            config.networks[i]
+                .contracts
+                .iter()
+                .map(|contract| (contract.kind, contract.version))
+                .collect()
+        };
+
+        // The V1 Emission Funds contract does not exist, so only the V2 contract is monitored.
+        assert_eq!(config.networks[0].network, SOKOL_NETWORK);
+        assert_eq!(
+            contracts(0),
+            vec![
+                (ContractType::Threshold, V1_VERSION),
+                (ContractType::Threshold, V2_VERSION),
+                (ContractType::Emission, V2_VERSION),
+            ],
+        );
+
+        // No V1 contracts were deployed to the xDai chain.
+        assert_eq!(config.networks[1].network, XDAI_NETWORK);
+        assert_eq!(
+            contracts(1),
+            vec![(ContractType::Threshold, V2_VERSION), (ContractType::Emission, V2_VERSION)],
+        );
+    }
 }
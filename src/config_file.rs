@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+use std::fs;
+
+use serde_derive::Deserialize;
+
+use crate::cli::Cli;
+use crate::config::{ContractType, ContractVersion, Network};
+use crate::error::{Error, Result};
+use crate::response::common::BallotType;
+
+/// The contents of a `poagov` TOML config file (given via the `--config` CLI argument).
+///
+/// Every setting in the config file is optional; settings that are missing from the config file
+/// are read from the environment (i.e. the `.env` file), and settings given via CLI arguments
+/// override the config file.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub state_dir: Option<String>,
+    /// Addresses that escalate the notifications of any ballot containing them, each formatted as
+    /// either "<address>" or "<address>:<label>".
+    pub watch_list: Option<Vec<String>>,
+    #[serde(default)]
+    pub networks: Vec<NetworkSection>,
+    pub email: Option<EmailSection>,
+    pub telegram: Option<TelegramSection>,
+    pub webhook: Option<WebhookSection>,
+}
+
+/// A `[[networks]]` table, defines a built-in network's settings or a custom network.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkSection {
+    /// Either the name of a built-in network ("core", "sokol", "xdai") or of a custom network.
+    pub name: String,
+    pub endpoint: Option<String>,
+    pub chain_id: Option<u64>,
+    pub block_time: Option<u64>,
+    /// The directory containing the network's `v1/` and `v2/` contract ABI directories, defaults
+    /// to the `abis/` directory.
+    pub abi_dir: Option<String>,
+    pub proxy_storage: Option<ProxyStorageSection>,
+    /// The Slack or Mattermost incoming-webhook URLs that the network's notifications are posted
+    /// to.
+    pub chat_webhooks: Option<Vec<String>>,
+    /// The block explorer linked to in HTML emails, e.g. "https://blockscout.com/poa/core".
+    pub explorer_url: Option<String>,
+    /// The governance DApp's ballot page linked to in HTML emails, "{ballot_id}" and "{contract}"
+    /// are replaced by the ballot's id and contract address.
+    pub ballot_url: Option<String>,
+    #[serde(default)]
+    pub contracts: Vec<ContractSection>,
+}
+
+/// A `[[networks.contracts]]` table.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractSection {
+    pub kind: ContractType,
+    pub version: ContractVersion,
+    pub address: Option<String>,
+    /// The path to the contract's ABI file, defaults to the ABI found in the `abis/` directory.
+    pub abi: Option<String>,
+}
+
+/// A `[networks.proxy_storage]` table, the network's `ProxyStorage` contract addresses (used by
+/// the `--discover-contracts` CLI flag).
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProxyStorageSection {
+    pub v1: Option<String>,
+    pub v2: Option<String>,
+}
+
+/// The `[email]` table, configures email notifications.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailSection {
+    /// Each recipient is formatted as either "<email>" or "<email>:<voting key>".
+    pub recipients: Option<Vec<String>>,
+    pub smtp_host_domain: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    /// One of: none, opportunistic, required, wrapper. Defaults to "required".
+    pub smtp_security: Option<String>,
+    /// One of: plain, login, xoauth2. Defaults to "plain".
+    pub smtp_auth_mechanism: Option<String>,
+    pub outgoing_email_address: Option<String>,
+    /// The number of times an email is attempted before it is moved to the dead-letter file,
+    /// defaults to 5.
+    pub max_attempts: Option<u32>,
+    pub templates: Option<EmailTemplatesSection>,
+    /// Subscription filters keyed by recipient email address, recipients without a subscription
+    /// are sent every notification.
+    #[serde(default)]
+    pub subscriptions: HashMap<String, SubscriptionSection>,
+}
+
+/// The `[email.templates]` table, the Handlebars templates used to render each email's subject and
+/// body.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailTemplatesSection {
+    /// An inline subject template.
+    pub subject: Option<String>,
+    /// The path to a body template file.
+    pub body: Option<String>,
+    /// Inline subject templates keyed by ballot type (e.g. "AddKey").
+    #[serde(default)]
+    pub subjects: HashMap<String, String>,
+    /// Body template file paths keyed by ballot type.
+    #[serde(default)]
+    pub bodies: HashMap<String, String>,
+}
+
+/// An `[email.subscriptions."<email>"]` table, restricts the notifications sent to a recipient.
+/// A notification is sent to the recipient only if it matches every non-empty list.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubscriptionSection {
+    /// Network names, e.g. "core".
+    #[serde(default)]
+    pub networks: Vec<String>,
+    #[serde(default)]
+    pub contract_types: Vec<ContractType>,
+    /// Ballot type names, e.g. "AddKey".
+    #[serde(default)]
+    pub ballot_types: Vec<BallotType>,
+}
+
+/// The `[telegram]` table, configures Telegram notifications.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TelegramSection {
+    pub bot_token: Option<String>,
+    /// Each chat id is either a numeric id (e.g. "-1001234567890") or a public channel's username
+    /// (e.g. "@poa_governance").
+    pub chat_ids: Option<Vec<String>>,
+    /// Defaults to "https://api.telegram.org".
+    pub api_url: Option<String>,
+}
+
+/// The `[webhook]` table, configures JSON webhook notifications.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookSection {
+    pub urls: Option<Vec<String>>,
+    /// The key used to sign each request body using HMAC-SHA256.
+    pub secret: Option<String>,
+    /// The number of times a failed request is retried, defaults to 3.
+    pub max_retries: Option<u32>,
+}
+
+impl ConfigFile {
+    /// Reads the config file given by the `--config` CLI argument, returns `None` if no config file
+    /// was given.
+    pub fn from_cli(cli: &Cli) -> Result<Option<Self>> {
+        match cli.config_file() {
+            Some(path) => ConfigFile::read(path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn read(path: &str) -> Result<Self> {
+        let contents = fs::read_to_string(path).map_err(Error::FailedToReadConfigFile)?;
+        ConfigFile::parse(&contents)
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        toml::from_str(contents).map_err(|e| Error::InvalidConfigFile(e.to_string()))
+    }
+
+    pub fn network(&self, network: &Network) -> Option<&NetworkSection> {
+        self.networks.iter().find(|section| match Network::from_name(&section.name) {
+            Ok(ref section_network) => section_network == network,
+            Err(_) => false,
+        })
+    }
+}
+
+impl NetworkSection {
+    pub fn contract(
+        &self,
+        kind: ContractType,
+        version: ContractVersion,
+    ) -> Option<&ContractSection> {
+        self.contracts
+            .iter()
+            .find(|section| section.kind == kind && section.version == version)
+    }
+}
+
+impl ProxyStorageSection {
+    pub fn address(&self, version: ContractVersion) -> Option<String> {
+        match version {
+            ContractVersion::V1 => self.v1.clone(),
+            ContractVersion::V2 => self.v2.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConfigFile;
+    use crate::config::{ContractType, ContractVersion, Network};
+
+    const CONFIG_FILE: &str = r#"
+        state_dir = "/var/lib/poagov"
+        watch_list = ["0x82e4e61e7f5139ff0a4157a5bc687ef42294c248:our mining key"]
+
+        [[networks]]
+        name = "sokol"
+        endpoint = "https://sokol.poa.network"
+        block_time = 5
+
+        [[networks.contracts]]
+        kind = "keys"
+        version = "v2"
+        address = "0xb974df531c1b27324618175b442edf95f7f7a621"
+
+        [[networks.contracts]]
+        kind = "emission"
+        version = "v2"
+        abi = "abis/v2/VotingToManageEmissionFunds.abi.json"
+
+        [[networks]]
+        name = "xdai"
+
+        [[networks]]
+        name = "my-testnet"
+        endpoint = "http://localhost:8545"
+        chain_id = 12345
+        abi_dir = "/etc/poagov/abis"
+        chat_webhooks = ["https://chat.example.com/hooks/abc"]
+        explorer_url = "http://localhost:4000"
+
+        [networks.proxy_storage]
+        v2 = "0x0000000000000000000000000000000000000002"
+
+        [[networks.contracts]]
+        kind = "threshold"
+        version = "v2"
+        address = "0x0000000000000000000000000000000000000001"
+
+        [email]
+        recipients = ["alice@poa.network", "bob@poa.network"]
+        smtp_port = 587
+        max_attempts = 8
+
+        [email.templates]
+        subject = "[{{network}}] {{event}}"
+
+        [email.templates.subjects]
+        AddKey = "[{{network}}] New AddKey ballot #{{ballot_id}} ends {{end_date}}"
+
+        [email.subscriptions."treasury@poa.network"]
+        ballot_types = ["Emission"]
+
+        [email.subscriptions."keys-team@poa.network"]
+        networks = ["core"]
+        contract_types = ["keys"]
+
+        [telegram]
+        chat_ids = ["-1001234567890", "@poa_governance"]
+
+        [webhook]
+        urls = ["https://alerts.example.com/poagov"]
+        max_retries = 5
+    "#;
+
+    #[test]
+    fn test_parse_config_file() {
+        let file = ConfigFile::parse(CONFIG_FILE).unwrap();
+        assert_eq!(file.state_dir, Some("/var/lib/poagov".to_string()));
+        assert_eq!(file.watch_list.as_ref().unwrap().len(), 1);
+        assert_eq!(file.networks.len(), 3);
+
+        let sokol = file.network(&Network::SOKOL).unwrap();
+        assert_eq!(sokol.block_time, Some(5));
+        assert_eq!(sokol.contracts.len(), 2);
+        let keys = sokol.contract(ContractType::Keys, ContractVersion::V2).unwrap();
+        assert!(keys.address.is_some());
+        assert!(keys.abi.is_none());
+        assert!(sokol.contract(ContractType::Keys, ContractVersion::V1).is_none());
+
+        let xdai = file.network(&Network::XDAI).unwrap();
+        assert!(xdai.endpoint.is_none());
+        assert!(xdai.contracts.is_empty());
+        assert!(file.network(&Network::CORE).is_none());
+
+        let testnet = file.network(&Network::from_name("My-Testnet").unwrap()).unwrap();
+        assert_eq!(testnet.chain_id, Some(12345));
+        assert_eq!(testnet.chat_webhooks.as_ref().unwrap().len(), 1);
+        assert_eq!(testnet.explorer_url, Some("http://localhost:4000".to_string()));
+        assert!(testnet.ballot_url.is_none());
+        assert_eq!(testnet.abi_dir, Some("/etc/poagov/abis".to_string()));
+        let proxy_storage = testnet.proxy_storage.as_ref().unwrap();
+        assert!(proxy_storage.address(ContractVersion::V1).is_none());
+        assert!(proxy_storage.address(ContractVersion::V2).is_some());
+
+        let email = file.email.unwrap();
+        assert_eq!(email.recipients.unwrap().len(), 2);
+        assert_eq!(email.smtp_port, Some(587));
+        assert_eq!(email.max_attempts, Some(8));
+        assert!(email.smtp_host_domain.is_none());
+        let templates = email.templates.unwrap();
+        assert!(templates.subject.is_some());
+        assert!(templates.body.is_none());
+        assert_eq!(templates.subjects.len(), 1);
+        assert_eq!(email.subscriptions.len(), 2);
+        let keys_team = &email.subscriptions["keys-team@poa.network"];
+        assert_eq!(keys_team.networks, vec!["core".to_string()]);
+        assert_eq!(keys_team.contract_types, vec![ContractType::Keys]);
+        assert!(keys_team.ballot_types.is_empty());
+
+        let telegram = file.telegram.unwrap();
+        assert_eq!(telegram.chat_ids.unwrap().len(), 2);
+        assert!(telegram.api_url.is_none());
+
+        let webhook = file.webhook.unwrap();
+        assert_eq!(webhook.urls.unwrap().len(), 1);
+        assert_eq!(webhook.max_retries, Some(5));
+        assert!(webhook.secret.is_none());
+
+        assert!(ConfigFile::parse("unknown_setting = true").is_err());
+    }
+}
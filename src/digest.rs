@@ -0,0 +1,234 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use serde_json as json;
+use web3::types::U256;
+
+use crate::config::EmailDigest;
+use crate::error::{Error, Result};
+use crate::html::wrap_html;
+use crate::state::write_atomically;
+
+// The name of the file (within the state directory) that the queued digest entries are stored in.
+const DIGEST_FILE_NAME: &str = "digest";
+
+/// A notification email that has been rendered and queued for the next digest.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DigestEntry {
+    pub network: String,
+    pub block_number: U256,
+    pub subject: String,
+    pub text: String,
+    pub html_section: String,
+    /// The recipients that should not be sent this entry (see `Notification::should_notify` and
+    /// `Notification::matches_subscription`).
+    pub excluded: Vec<String>,
+}
+
+/// Batches notification emails so that each recipient is sent one digest email per block-window or
+/// per interval, rather than one email per notification. The queued entries are stored in the
+/// state directory (one JSON object per line), so that a notification which was reported as
+/// delivered is still sent if `poagov` stops before the digest is sent.
+#[derive(Debug)]
+pub struct DigestQueue {
+    mode: EmailDigest,
+    path: PathBuf,
+    entries: Vec<DigestEntry>,
+    started_at: DateTime<Utc>,
+}
+
+impl DigestQueue {
+    /// Loads the entries that were queued before `poagov` last stopped. If no digest file exists,
+    /// we return an empty queue.
+    pub fn open(state_dir: &str, mode: EmailDigest) -> Result<Self> {
+        let path = Path::new(state_dir).join(DIGEST_FILE_NAME);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(Error::FailedToReadDigest(e)),
+        };
+        let entries = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| json::from_str(line).map_err(|_| Error::InvalidDigestEntry(line.into())))
+            .collect::<Result<Vec<DigestEntry>>>()?;
+        Ok(DigestQueue {
+            mode,
+            path,
+            entries,
+            started_at: Utc::now(),
+        })
+    }
+
+    /// Queues `entry` and saves the queue. If the queue cannot be saved, the entry is not queued.
+    pub fn push(&mut self, entry: DigestEntry) -> Result<()> {
+        self.entries.push(entry);
+        if let Err(e) = self.save() {
+            self.entries.pop();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns `true` if the queued entries should be sent, this is checked after each
+    /// block-window.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        match self.mode {
+            EmailDigest::PerWindow => !self.is_empty(),
+            EmailDigest::Interval(interval) => {
+                !self.is_empty() && now >= self.started_at + interval
+            }
+        }
+    }
+
+    /// Removes and returns every queued entry grouped by network, each network's entries are
+    /// ordered by ascending block number. Starts the next digest interval at `now`. The removed
+    /// entries stay in the digest file until `save` is called, i.e. once they have been sent.
+    pub fn take(&mut self, now: DateTime<Utc>) -> Vec<DigestEntry> {
+        self.started_at = now;
+        let mut entries: Vec<DigestEntry> = self.entries.drain(..).collect();
+        // `sort_by` is stable, so entries with the same block number keep the order in which they
+        // were queued.
+        entries.sort_by(|entry1, entry2| {
+            (&entry1.network, entry1.block_number).cmp(&(&entry2.network, entry2.block_number))
+        });
+        entries
+    }
+
+    /// Writes the queued entries to the digest file.
+    pub fn save(&self) -> Result<()> {
+        if let Some(state_dir) = self.path.parent() {
+            fs::create_dir_all(state_dir).map_err(|e| Error::FailedToCreateStateDir(e))?;
+        }
+        let contents: String = self
+            .entries
+            .iter()
+            .map(|entry| format!("{}\n", json::to_string(entry).unwrap()))
+            .collect();
+        write_atomically(&self.path, contents.as_bytes()).map_err(|e| Error::FailedToWriteDigest(e))
+    }
+}
+
+/// Formats the subject, text part, and HTML part of a digest email for one recipient, along with
+/// the number of entries included in the digest (entries that exclude the recipient are left
+/// out). Returns `None` if none of the entries are sent to the recipient.
+pub fn format_digest(
+    entries: &[DigestEntry],
+    recipient: &str,
+) -> Option<(String, String, String, usize)> {
+    let entries: Vec<&DigestEntry> = entries
+        .iter()
+        .filter(|entry| !entry.excluded.iter().any(|excluded| excluded == recipient))
+        .collect();
+    if entries.is_empty() {
+        return None;
+    }
+    let mut networks: Vec<&str> = vec![];
+    for entry in entries.iter() {
+        if !networks.contains(&entry.network.as_str()) {
+            networks.push(&entry.network);
+        }
+    }
+    let subject = format!(
+        "POA Network Governance Digest ({}): {} notification{}",
+        networks.join(", "),
+        entries.len(),
+        if entries.len() == 1 { "" } else { "s" },
+    );
+    let text = entries
+        .iter()
+        .map(|entry| format!("{}\n\n{}", entry.subject, entry.text.trim_right()))
+        .collect::<Vec<String>>()
+        .join("\n\n----------------------------------------\n\n");
+    let sections: Vec<String> = entries.iter().map(|entry| entry.html_section.clone()).collect();
+    Some((subject, format!("{}\n", text), wrap_html(&sections), entries.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use chrono::{Duration, Utc};
+    use web3::types::U256;
+
+    use super::{format_digest, DigestEntry, DigestQueue};
+    use crate::config::EmailDigest;
+
+    fn entry(network: &str, block_number: u64, excluded: &[&str]) -> DigestEntry {
+        DigestEntry {
+            network: network.to_string(),
+            block_number: U256::from(block_number),
+            subject: format!("{} block {}", network, block_number),
+            text: format!("Block Number: {}\n", block_number),
+            html_section: format!("<p>{}</p>\n", block_number),
+            excluded: excluded.iter().map(|email| email.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_digest_queue() {
+        let state_dir = env::temp_dir().join("poagov-test-digest-queue");
+        let _ = fs::remove_dir_all(&state_dir);
+        let state_dir = state_dir.to_str().unwrap();
+        let mode = EmailDigest::Interval(Duration::hours(1));
+        let start = Utc::now();
+        let mut queue = DigestQueue::open(state_dir, mode).unwrap();
+        queue.started_at = start;
+        assert!(!queue.is_due(start + Duration::hours(2)));
+
+        queue.push(entry("Sokol", 20, &[])).unwrap();
+        queue.push(entry("Core", 30, &[])).unwrap();
+        queue.push(entry("Sokol", 10, &["bob@poa.network"])).unwrap();
+        assert!(!queue.is_due(start + Duration::minutes(59)));
+        assert!(queue.is_due(start + Duration::hours(1)));
+
+        let entries = queue.take(start + Duration::hours(1));
+        let order: Vec<u64> = entries.iter().map(|entry| entry.block_number.low_u64()).collect();
+        assert_eq!(order, vec![30, 10, 20]);
+        assert!(queue.is_empty());
+        queue.push(entry("Core", 40, &[])).unwrap();
+        assert!(!queue.is_due(start + Duration::minutes(90)));
+
+        let (subject, text, html, n_entries) =
+            format_digest(&entries, "alice@poa.network").unwrap();
+        assert_eq!(subject, "POA Network Governance Digest (Core, Sokol): 3 notifications");
+        assert!(text.find("Sokol block 10").unwrap() < text.find("Sokol block 20").unwrap());
+        assert_eq!(html.matches("<hr>").count(), 2);
+        assert_eq!(n_entries, 3);
+        let (subject, _, _, n_entries) = format_digest(&entries, "bob@poa.network").unwrap();
+        assert!(subject.ends_with("2 notifications"));
+        assert_eq!(n_entries, 2);
+        assert!(format_digest(&entries[1..2], "bob@poa.network").is_none());
+    }
+
+    #[test]
+    fn test_digest_queue_survives_restarts() {
+        let state_dir = env::temp_dir().join("poagov-test-digest-restart");
+        let _ = fs::remove_dir_all(&state_dir);
+        let state_dir = state_dir.to_str().unwrap();
+
+        let mut queue = DigestQueue::open(state_dir, EmailDigest::PerWindow).unwrap();
+        queue.push(entry("Sokol", 10, &["bob@poa.network"])).unwrap();
+        queue.push(entry("Core", 20, &[])).unwrap();
+
+        // Entries are kept in the digest file until they have been taken and the queue saved.
+        let mut queue = DigestQueue::open(state_dir, EmailDigest::PerWindow).unwrap();
+        assert!(queue.is_due(Utc::now()));
+        let entries = queue.take(Utc::now());
+        assert_eq!(entries[0], entry("Core", 20, &[]));
+        assert_eq!(entries[1], entry("Sokol", 10, &["bob@poa.network"]));
+        let mut queue = DigestQueue::open(state_dir, EmailDigest::PerWindow).unwrap();
+        assert_eq!(queue.take(Utc::now()), entries);
+        queue.save().unwrap();
+        let queue = DigestQueue::open(state_dir, EmailDigest::PerWindow).unwrap();
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,351 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use web3::types::{Address, U256};
+
+use crate::client::RpcClient;
+use crate::config::{ContractType, ContractVersion, NetworkConfig, PoaContract, ProxyStorage};
+use crate::error::{Error, Result};
+use crate::logger::Logger;
+use crate::response::common::{u256_to_contract_type, BallotState};
+use crate::response::v1::{self, VotingState};
+use crate::response::v2::{self, BallotInfo};
+
+// How often to re-read the voting contract addresses from `ProxyStorage`. A voting contract's
+// address only changes when a proxy ballot to upgrade the contract is finalized.
+const REDISCOVERY_INTERVAL_MINS: i64 = 10;
+
+/// An accepted proxy ballot that changed the contract stored in `ProxyStorage` for one of the
+/// voting contract types.
+#[derive(Clone, Debug)]
+pub struct ProxyUpgrade {
+    pub version: ContractVersion,
+    pub target: ContractType,
+    /// The ballot's proposed value, i.e. the address of the target contract's new implementation.
+    pub new_addr: Address,
+    pub ballot_id: U256,
+    pub block_number: U256,
+    /// The final state of the proxy ballot.
+    pub ballot_state: BallotState,
+}
+
+impl ProxyUpgrade {
+    /// V1 - returns `None` if the ballot was not an accepted proxy ballot targeting a voting
+    /// contract.
+    pub fn from_voting_state(
+        voting_state: &VotingState,
+        ballot_id: U256,
+        block_number: U256,
+    ) -> Option<Self> {
+        if let VotingState::Proxy(state) = voting_state {
+            if let v1::QuorumState::Accepted = state.quorum_state {
+                return Some(ProxyUpgrade {
+                    version: ContractVersion::V1,
+                    target: u256_to_contract_type(state.contract_type)?,
+                    new_addr: state.proposed_value,
+                    ballot_id,
+                    block_number,
+                    ballot_state: voting_state.clone().into(),
+                });
+            }
+        }
+        None
+    }
+
+    /// V2 - returns `None` if the ballot was not an accepted proxy ballot targeting a voting
+    /// contract.
+    pub fn from_ballot_info(
+        ballot_info: &BallotInfo,
+        quorum_state: v2::QuorumState,
+        ballot_id: U256,
+        block_number: U256,
+    ) -> Option<Self> {
+        if let (BallotInfo::Proxy(info), v2::QuorumState::Accepted) = (ballot_info, quorum_state) {
+            return Some(ProxyUpgrade {
+                version: ContractVersion::V2,
+                target: u256_to_contract_type(info.contract_type)?,
+                new_addr: info.proposed_value,
+                ballot_id,
+                block_number,
+                ballot_state: ballot_info.clone().into(),
+            });
+        }
+        None
+    }
+}
+
+/// A monitored contract whose address was changed by an accepted proxy ballot.
+#[derive(Clone, Debug)]
+pub struct ContractAddressChange {
+    pub upgrade: ProxyUpgrade,
+    pub old_addr: Address,
+    pub new_addr: Address,
+}
+
+/// Keeps a network's voting contract addresses in sync with the addresses stored in the network's
+/// `ProxyStorage` contracts.
+pub struct ContractDiscovery<'a> {
+    client: &'a RpcClient,
+    network_config: &'a NetworkConfig,
+    logger: Arc<Mutex<Logger>>,
+    last_discovered_at: Option<DateTime<Utc>>,
+}
+
+impl<'a> ContractDiscovery<'a> {
+    pub fn new(
+        client: &'a RpcClient,
+        network_config: &'a NetworkConfig,
+        logger: Arc<Mutex<Logger>>,
+    ) -> Self {
+        ContractDiscovery {
+            client,
+            network_config,
+            logger,
+            last_discovered_at: None,
+        }
+    }
+
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        match self.last_discovered_at {
+            Some(last_discovered_at) => {
+                now - last_discovered_at >= Duration::minutes(REDISCOVERY_INTERVAL_MINS)
+            }
+            None => true,
+        }
+    }
+
+    /// Returns the address of `proxy_storage`, if the address was not configured it is read from
+    /// one of the voting contracts (every voting contract stores its `ProxyStorage` address).
+    fn proxy_storage_addr<'b, I>(
+        &self,
+        proxy_storage: &ProxyStorage,
+        contracts: I,
+    ) -> Result<Address>
+    where
+        I: IntoIterator<Item = &'b PoaContract>,
+    {
+        if let Some(addr) = proxy_storage.addr {
+            return Ok(addr);
+        }
+        let known_contract = contracts.into_iter().find(|contract| {
+            contract.version == proxy_storage.version && !contract.is_undiscovered()
+        });
+        match known_contract {
+            Some(contract) => self.client.get_proxy_storage_addr(contract),
+            None => Err(Error::CannotDiscoverContracts {
+                network: self.network_config.network.to_string(),
+                version: format!("{:?}", proxy_storage.version),
+            }),
+        }
+    }
+
+    /// Reads the current address of each contract in `contracts` (with `proxy_storage`'s version)
+    /// from `ProxyStorage` and updates the contracts whose address has changed.
+    fn sync(
+        &self,
+        contracts: &mut [PoaContract],
+        proxy_storage: &ProxyStorage,
+        proxy_storage_addr: Address,
+    ) -> Result<()> {
+        let contracts = contracts
+            .iter_mut()
+            .filter(|contract| contract.version == proxy_storage.version);
+        for contract in contracts {
+            let addr = self.client.get_voting_contract_addr(
+                proxy_storage,
+                proxy_storage_addr,
+                contract.kind,
+            )?;
+            if addr != contract.addr {
+                contract.addr = addr;
+                self.logger
+                    .lock()
+                    .unwrap()
+                    .log_discovered_contract(&self.network_config.network, contract);
+            }
+        }
+        Ok(())
+    }
+
+    /// Updates the address of each monitored contract (`contracts`) and each Proxy contract that
+    /// is only checked for upgrades (`proxy_contracts`) whose address stored in `ProxyStorage` has
+    /// changed (when `--discover-contracts` is set). Discovery runs the first time this method is
+    /// called, then at most once every `REDISCOVERY_INTERVAL_MINS`.
+    pub fn update(
+        &mut self,
+        contracts: &mut [PoaContract],
+        proxy_contracts: &mut [PoaContract],
+    ) -> Result<()> {
+        let now = Utc::now();
+        if !self.network_config.discover_contracts || !self.is_due(now) {
+            return Ok(());
+        }
+        for proxy_storage in self.network_config.proxy_storages.iter() {
+            let known_contracts = contracts.iter().chain(proxy_contracts.iter());
+            let proxy_storage_addr = self.proxy_storage_addr(proxy_storage, known_contracts)?;
+            self.sync(contracts, proxy_storage, proxy_storage_addr)?;
+            self.sync(proxy_contracts, proxy_storage, proxy_storage_addr)?;
+        }
+        self.last_discovered_at = Some(now);
+        Ok(())
+    }
+
+    /// Switches the contract in `contracts` targeted by an accepted proxy ballot to the ballot's
+    /// proposed address. Returns `None` if the ballot's target contract type is not in `contracts`
+    /// or if its address did not change.
+    ///
+    /// A V1 proxy ballot stores its proposed address in `ProxyStorage`, replacing the target
+    /// contract. V2 voting contracts are upgraded in place (the proposed address becomes the
+    /// implementation behind the contract's proxy), so their address does not change.
+    pub fn follow_proxy_upgrade(
+        &self,
+        contracts: &mut [PoaContract],
+        upgrade: &ProxyUpgrade,
+    ) -> Option<ContractAddressChange> {
+        if upgrade.version != ContractVersion::V1 {
+            return None;
+        }
+        let contract = contracts.iter_mut().find(|contract| {
+            contract.version == upgrade.version && contract.kind == upgrade.target
+        })?;
+        if contract.addr == upgrade.new_addr {
+            return None;
+        }
+        let old_addr = contract.addr;
+        contract.addr = upgrade.new_addr;
+        self.logger
+            .lock()
+            .unwrap()
+            .log_discovered_contract(&self.network_config.network, contract);
+        Some(ContractAddressChange {
+            upgrade: upgrade.clone(),
+            old_addr,
+            new_addr: upgrade.new_addr,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{Duration, Utc};
+    use web3::types::{Address, U256};
+
+    use super::{ContractDiscovery, ProxyUpgrade, REDISCOVERY_INTERVAL_MINS};
+    use crate::cli::parse_cli_from;
+    use crate::client::RpcClient;
+    use crate::config::{Config, ContractType, ContractVersion, PoaContract};
+    use crate::logger::Logger;
+    use crate::response::v1;
+    use crate::response::v2::{self, BallotInfo, ProxyBallotInfo};
+    use crate::tests::{setup, v1_keys_proxy_voting_state, SOKOL_NETWORK};
+
+    #[test]
+    fn test_rediscovery_interval() {
+        setup();
+        let cli = parse_cli_from(vec!["poagov", "--sokol", "-t", "--latest"]);
+        let config = Config::new(&cli).unwrap();
+        let network_config = &config.networks[0];
+        let client = RpcClient::new(network_config.endpoint.clone());
+        let logger = Arc::new(Mutex::new(Logger::new(&config)));
+        let mut discovery = ContractDiscovery::new(&client, network_config, logger);
+        let now = Utc::now();
+
+        // Discovery always runs the first time that it is checked.
+        assert!(discovery.is_due(now));
+        discovery.last_discovered_at = Some(now);
+        assert!(!discovery.is_due(now));
+        let interval = Duration::minutes(REDISCOVERY_INTERVAL_MINS);
+        assert!(!discovery.is_due(now + interval - Duration::seconds(1)));
+        assert!(discovery.is_due(now + interval));
+    }
+
+    #[test]
+    fn test_proxy_upgrade_from_voting_state() {
+        let new_addr = Address::from(1);
+        let accepted = v1_keys_proxy_voting_state(v1::QuorumState::Accepted, new_addr);
+        let upgrade = ProxyUpgrade::from_voting_state(&accepted, U256::from(4), U256::from(100))
+            .expect("an accepted proxy ballot is an upgrade");
+        assert_eq!(upgrade.version, ContractVersion::V1);
+        assert_eq!(upgrade.target, ContractType::Keys);
+        assert_eq!(upgrade.new_addr, new_addr);
+        assert_eq!(upgrade.ballot_id, U256::from(4));
+        assert_eq!(upgrade.block_number, U256::from(100));
+
+        let rejected = v1_keys_proxy_voting_state(v1::QuorumState::Rejected, new_addr);
+        let upgrade = ProxyUpgrade::from_voting_state(&rejected, U256::from(4), U256::from(100));
+        assert!(upgrade.is_none());
+    }
+
+    #[test]
+    fn test_proxy_upgrade_from_ballot_info() {
+        let end_time = Utc::now() - Duration::days(1);
+        let ballot_info: BallotInfo = ProxyBallotInfo {
+            start_time: end_time - Duration::days(2),
+            end_time,
+            total_voters: U256::from(3),
+            progress: U256::from(3),
+            is_finalized: true,
+            proposed_value: Address::from(1),
+            contract_type: U256::from(3),
+            creator: Address::zero(),
+            memo: "upgrade the threshold contract".to_string(),
+            can_be_finalized_now: false,
+            already_voted: false,
+        }
+        .into();
+        let (ballot_id, block_number) = (U256::from(4), U256::from(100));
+
+        let upgrade = ProxyUpgrade::from_ballot_info(
+            &ballot_info,
+            v2::QuorumState::Accepted,
+            ballot_id,
+            block_number,
+        )
+        .expect("an accepted proxy ballot is an upgrade");
+        assert_eq!(upgrade.version, ContractVersion::V2);
+        assert_eq!(upgrade.target, ContractType::Threshold);
+        assert_eq!(upgrade.new_addr, Address::from(1));
+
+        let rejected = v2::QuorumState::Rejected;
+        assert!(
+            ProxyUpgrade::from_ballot_info(&ballot_info, rejected, ballot_id, block_number)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_follow_proxy_upgrade() {
+        setup();
+        let cli = parse_cli_from(vec!["poagov", "--sokol", "-t", "--v2", "--latest"]);
+        let config = Config::new(&cli).unwrap();
+        let network_config = &config.networks[0];
+        let client = RpcClient::new(network_config.endpoint.clone());
+        let logger = Arc::new(Mutex::new(Logger::new(&config)));
+        let discovery = ContractDiscovery::new(&client, network_config, logger);
+        let keys_contract =
+            PoaContract::read(ContractType::Keys, &SOKOL_NETWORK, ContractVersion::V1).unwrap();
+        let old_addr = keys_contract.addr;
+        let mut contracts = vec![keys_contract, network_config.contracts[0].clone()];
+
+        // The V1 Keys contract is replaced by the ballot's proposed address.
+        let new_addr = Address::from(1);
+        let voting_state = v1_keys_proxy_voting_state(v1::QuorumState::Accepted, new_addr);
+        let upgrade =
+            ProxyUpgrade::from_voting_state(&voting_state, U256::from(4), U256::from(100)).unwrap();
+        let change = discovery.follow_proxy_upgrade(&mut contracts, &upgrade).unwrap();
+        assert_eq!(change.old_addr, old_addr);
+        assert_eq!(change.new_addr, new_addr);
+        assert_eq!(contracts[0].addr, new_addr);
+        assert_eq!(contracts[1].addr, network_config.contracts[0].addr);
+
+        // Following the same upgrade again does not change the contract's address.
+        assert!(discovery.follow_proxy_upgrade(&mut contracts, &upgrade).is_none());
+
+        // An upgrade that targets a contract which is not in `contracts` is ignored.
+        let mut contracts = network_config.contracts.clone();
+        assert!(discovery.follow_proxy_upgrade(&mut contracts, &upgrade).is_none());
+        assert_eq!(contracts[0].addr, network_config.contracts[0].addr);
+    }
+}
@@ -0,0 +1,405 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use lettre::{SendableEmail, Transport as _Transport};
+use lettre::smtp::{ClientSecurity, ConnectionReuseParameters, SmtpClient, SmtpTransport};
+use lettre::smtp::authentication::{Credentials, Mechanism};
+use lettre::smtp::client::net::ClientTlsParameters;
+use lettre_email::{Email, EmailBuilder};
+use native_tls::TlsConnector;
+
+use crate::config::{Config, SmtpAuthMechanism, SmtpSecurity};
+use crate::digest::{format_digest, DigestEntry, DigestQueue};
+use crate::error::{Error, Result};
+use crate::html::{format_html, format_html_section};
+use crate::logger::Logger;
+use crate::notify::Notification;
+use crate::sink::{delivery_result, Sent, Sink};
+use crate::state::outbox::{Outbox, OutgoingEmail, Retry};
+use crate::templates::EmailTemplates;
+use crate::watch::{find_matches, WatchListMatch, WATCH_LIST_SUBJECT_PREFIX};
+
+/// The outcome of an attempt to send an email.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Delivery {
+    Sent,
+    /// The email failed to send and was added to the outbox.
+    Queued,
+    Failed,
+}
+
+/// Builds the SMTP transport used to send emails. SMTP authentication is only attempted when
+/// `credentials` are given (e.g. an internal relay may accept unauthenticated email on port 25).
+pub fn smtp_transport(
+    domain: &str,
+    port: u16,
+    security: SmtpSecurity,
+    mechanism: SmtpAuthMechanism,
+    credentials: Option<Credentials>,
+) -> Result<SmtpTransport> {
+    let tls_params = || -> Result<ClientTlsParameters> {
+        let tls = TlsConnector::new().map_err(|e| Error::FailedToBuildTls(e))?;
+        Ok(ClientTlsParameters::new(domain.to_string(), tls))
+    };
+    let security = match security {
+        SmtpSecurity::None => ClientSecurity::None,
+        SmtpSecurity::Opportunistic => ClientSecurity::Opportunistic(tls_params()?),
+        SmtpSecurity::Required => ClientSecurity::Required(tls_params()?),
+        SmtpSecurity::Wrapper => ClientSecurity::Wrapper(tls_params()?),
+    };
+    let mut client = SmtpClient::new((domain, port), security)
+        .map_err(|e| Error::FailedToResolveSmtpHostDomain(e))?
+        .connection_reuse(ConnectionReuseParameters::ReuseUnlimited);
+    if let Some(credentials) = credentials {
+        let mechanism = match mechanism {
+            SmtpAuthMechanism::Plain => Mechanism::Plain,
+            SmtpAuthMechanism::Login => Mechanism::Login,
+            SmtpAuthMechanism::Xoauth2 => Mechanism::Xoauth2,
+        };
+        client = client.authentication_mechanism(mechanism).credentials(credentials);
+    }
+    Ok(client.transport())
+}
+
+/// Sends notification emails (or email digests) to the configured email recipients via SMTP.
+/// Emails that fail to send are retried from the outbox (see `Outbox`).
+pub struct EmailSink<'a> {
+    config: &'a Config,
+    emailer: SmtpTransport,
+    templates: EmailTemplates,
+    digest: Option<DigestQueue>,
+    outbox: Outbox,
+    logger: Arc<Mutex<Logger>>,
+}
+
+impl<'a> EmailSink<'a> {
+    pub fn new(config: &'a Config, logger: Arc<Mutex<Logger>>) -> Result<Self> {
+        let credentials = match (&config.smtp_username, &config.smtp_password) {
+            (Some(username), Some(password)) => {
+                Some(Credentials::new(username.clone(), password.clone()))
+            }
+            _ => None,
+        };
+        let emailer = smtp_transport(
+            config.smtp_host_domain.as_ref().unwrap(),
+            config.smtp_port.unwrap(),
+            config.smtp_security,
+            config.smtp_auth_mechanism,
+            credentials,
+        )?;
+        let templates = EmailTemplates::new(&config.email_templates)?;
+        let digest = match config.email_digest {
+            Some(mode) => Some(DigestQueue::open(&config.state_dir, mode)?),
+            None => None,
+        };
+        let outbox = Outbox::open(&config.state_dir, config.email_max_attempts)?;
+        Ok(EmailSink {
+            config,
+            emailer,
+            templates,
+            digest,
+            outbox,
+            logger,
+        })
+    }
+
+    /// Renders a notification's subject, text body, and HTML body; both bodies are rendered from
+    /// the body template. When the notification's ballot contains a watched address, the subject
+    /// is prefixed with `WATCH_LIST_SUBJECT_PREFIX` and the alerts are prepended to both bodies.
+    fn render(
+        &self,
+        notif: &Notification,
+        alert_lines: &[String],
+    ) -> Result<(String, String, String)> {
+        let subject = self.templates.subject(notif, &self.config.watch_list)?;
+        let text = self.templates.body(notif, &self.config.watch_list)?;
+        let html = format_html(notif, &text, alert_lines);
+        if alert_lines.is_empty() {
+            Ok((subject, text, html))
+        } else {
+            Ok((
+                format!("{} {}", WATCH_LIST_SUBJECT_PREFIX, subject),
+                format!("{}\n\n{}", alert_lines.join("\n"), text),
+                html,
+            ))
+        }
+    }
+
+    /// Renders a notification's email and queues it for the next email digest. The notification
+    /// is skipped if none of the recipients should be sent it.
+    fn queue_digest_entry(&mut self, notif: &Notification) -> Result<Sent> {
+        let excluded: Vec<String> = self
+            .config
+            .email_recipients
+            .iter()
+            .filter(|recipient| {
+                !notif.matches_subscription(recipient) || !notif.should_notify(recipient)
+            })
+            .map(|recipient| recipient.email.clone())
+            .collect();
+        if excluded.len() == self.config.email_recipients.len() {
+            return Ok(Sent::Skipped);
+        }
+        let (subject, text, _) = self.render(notif, &[])?;
+        let html_section = format_html_section(notif, &text);
+        let entry = DigestEntry {
+            network: notif.network().to_string(),
+            block_number: notif.block_number(),
+            subject,
+            text,
+            html_section,
+            excluded,
+        };
+        self.digest.as_mut().unwrap().push(entry)?;
+        Ok(Sent::Delivered)
+    }
+
+    /// Sends every queued digest entry, one email per recipient. The entries are removed from the
+    /// digest file once each recipient's digest has been sent or added to the outbox.
+    fn send_digest(&mut self) {
+        let entries = match self.digest {
+            Some(ref mut digest) if !digest.is_empty() => digest.take(Utc::now()),
+            _ => return,
+        };
+        for recipient in self.config.email_recipients.iter() {
+            let (subject, text, html, n_entries) = match format_digest(&entries, &recipient.email) {
+                Some(digest) => digest,
+                None => continue,
+            };
+            let email = OutgoingEmail {
+                recipient: recipient.email.clone(),
+                subject,
+                text,
+                html,
+                high_priority: false,
+            };
+            if self.deliver(email) == Delivery::Sent {
+                self.logger
+                    .lock()
+                    .unwrap()
+                    .log_digest_email_sent(&recipient.email, n_entries);
+            }
+        }
+        if let Err(e) = self.digest.as_ref().unwrap().save() {
+            self.logger.lock().unwrap().log_failed_to_update_digest(e);
+        }
+    }
+
+    /// Builds and sends an email, logging whether or not it was sent. An email that fails to send
+    /// is added to the outbox to be retried.
+    fn deliver(&mut self, email: OutgoingEmail) -> Delivery {
+        let built = match self.build_email(&email) {
+            Ok(built) => built,
+            Err(e) => {
+                self.logger.lock().unwrap().log_failed_to_build_email(e);
+                return Delivery::Failed;
+            }
+        };
+        let e = match self.send_email(built) {
+            Ok(()) => return Delivery::Sent,
+            Err(e) => e,
+        };
+        let recipient = email.recipient.clone();
+        let retry = self.outbox.push(email, &e, Utc::now());
+        self.logger
+            .lock()
+            .unwrap()
+            .log_failed_to_send_email(&recipient, e);
+        if self.log_retry(&recipient, 1, retry) {
+            Delivery::Queued
+        } else {
+            Delivery::Failed
+        }
+    }
+
+    /// Resends each email in the outbox that is due to be retried.
+    fn retry_outbox(&mut self) {
+        let now = Utc::now();
+        for entry in self.outbox.due(now) {
+            let recipient = entry.email.recipient.clone();
+            let attempts = entry.attempts + 1;
+            let res = self
+                .build_email(&entry.email)
+                .and_then(|email| self.send_email(email));
+            match res {
+                Ok(()) => {
+                    self.logger.lock().unwrap().log_email_sent(&recipient);
+                    if let Err(e) = self.outbox.remove(entry.id) {
+                        self.logger.lock().unwrap().log_failed_to_update_outbox(e);
+                    }
+                }
+                Err(e) => {
+                    let retry = self.outbox.retry_failed(entry, &e, now);
+                    self.logger
+                        .lock()
+                        .unwrap()
+                        .log_failed_to_send_email(&recipient, e);
+                    self.log_retry(&recipient, attempts, retry);
+                }
+            }
+        }
+    }
+
+    /// Logs the outcome of adding a failed email to the outbox, returns `true` if the email will
+    /// be retried.
+    fn log_retry(&self, recipient: &str, attempts: u32, retry: Result<Retry>) -> bool {
+        let mut logger = self.logger.lock().unwrap();
+        match retry {
+            Ok(Retry::Scheduled(next_attempt_at)) => {
+                logger.log_email_queued_for_retry(recipient, attempts, next_attempt_at);
+                true
+            }
+            Ok(Retry::DeadLettered) => {
+                logger.log_email_dead_lettered(recipient, attempts);
+                false
+            }
+            Err(e) => {
+                logger.log_failed_to_update_outbox(e);
+                false
+            }
+        }
+    }
+
+    /// Emails for watch-list alerts are sent with high priority headers.
+    fn build_email(&self, email: &OutgoingEmail) -> Result<Email> {
+        let outgoing_email = self.config.outgoing_email_addr.clone().unwrap();
+        let mut builder = EmailBuilder::new()
+            .to(email.recipient.as_str())
+            .from(outgoing_email.as_str())
+            .subject(email.subject.as_str())
+            .alternative(email.html.as_str(), email.text.as_str());
+        if email.high_priority {
+            builder = builder.header(("X-Priority", "1")).header(("Importance", "high"));
+        }
+        builder.build().map_err(|e| Error::FailedToBuildEmail(e))
+    }
+
+    fn send_email(&mut self, email: Email) -> Result<()> {
+        let email: SendableEmail = email.into();
+        match self.emailer.send(email) {
+            Ok(_response) => Ok(()),
+            Err(e) => Err(Error::FailedToSendEmail(e)),
+        }
+    }
+}
+
+impl<'a> Sink for EmailSink<'a> {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    /// When email digests are enabled, the notification is queued rather than sent, unless the
+    /// notification's ballot contains a watched address. Emails that failed to send but were
+    /// added to the outbox count as delivered.
+    fn send(&mut self, notif: &Notification) -> Result<Sent> {
+        let alert_lines: Vec<String> = find_matches(&self.config.watch_list, &notif.ballot_state())
+            .iter()
+            .map(WatchListMatch::alert_text)
+            .collect();
+        if self.digest.is_some() && alert_lines.is_empty() {
+            return match self.queue_digest_entry(notif) {
+                Ok(sent) => Ok(sent),
+                Err(e) => {
+                    self.logger.lock().unwrap().log_failed_to_queue_digest_entry(e);
+                    delivery_result(self.name(), 0, 1)
+                }
+            };
+        }
+        let (subject, text, html) = match self.render(notif, &alert_lines) {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                self.logger.lock().unwrap().log_failed_to_build_email(e);
+                return delivery_result(self.name(), 0, 1);
+            }
+        };
+        let mut n_sent = 0;
+        let mut n_failed = 0;
+        for recipient in self.config.email_recipients.iter() {
+            if !notif.matches_subscription(recipient) {
+                self.logger
+                    .lock()
+                    .unwrap()
+                    .log_recipient_not_subscribed(&recipient.email);
+                continue;
+            }
+            if !notif.should_notify(recipient) {
+                self.logger
+                    .lock()
+                    .unwrap()
+                    .log_recipient_already_voted(&recipient.email);
+                continue;
+            }
+            let email = OutgoingEmail {
+                recipient: recipient.email.clone(),
+                subject: subject.clone(),
+                text: text.clone(),
+                html: html.clone(),
+                high_priority: !alert_lines.is_empty(),
+            };
+            match self.deliver(email) {
+                Delivery::Sent => {
+                    self.logger.lock().unwrap().log_email_sent(&recipient.email);
+                    n_sent += 1;
+                }
+                Delivery::Queued => n_sent += 1,
+                Delivery::Failed => n_failed += 1,
+            }
+        }
+        delivery_result(self.name(), n_sent, n_failed)
+    }
+
+    /// Retries the emails in the outbox that are due, then sends the email digest if it is due.
+    fn end_block_window(&mut self) {
+        self.retry_outbox();
+        let due = match self.digest {
+            Some(ref digest) => digest.is_due(Utc::now()),
+            None => false,
+        };
+        if due {
+            self.send_digest();
+        }
+    }
+
+    /// Sends any notifications that are still waiting for the next email digest. Emails that are
+    /// still in the outbox are retried the next time `poagov` runs.
+    fn flush(&mut self) {
+        self.send_digest();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lettre::{SendableEmail, Transport as _Transport};
+    use lettre_email::EmailBuilder;
+
+    use super::smtp_transport;
+    use crate::config::{SmtpAuthMechanism, SmtpSecurity};
+    use crate::tests::stand_in_smtp_server;
+
+    #[test]
+    fn test_send_without_tls_or_auth() {
+        let (port, rx) = stand_in_smtp_server();
+        let mut emailer = smtp_transport(
+            "127.0.0.1",
+            port,
+            SmtpSecurity::None,
+            SmtpAuthMechanism::Plain,
+            None,
+        )
+        .unwrap();
+        let email: SendableEmail = EmailBuilder::new()
+            .to("alice@poa.network")
+            .from("poagov@poa.network")
+            .subject("POA Network Governance Notification (Sokol)")
+            .text("Memo: lower the threshold")
+            .build()
+            .unwrap()
+            .into();
+        emailer.send(email).unwrap();
+        let session = rx.recv().unwrap();
+        assert!(!session.contains("AUTH"));
+        assert!(!session.contains("STARTTLS"));
+        assert!(session.contains("RCPT TO:<alice@poa.network>"));
+        assert!(session.contains("Memo: lower the threshold"));
+    }
+}
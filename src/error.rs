@@ -2,32 +2,87 @@ pub type Result<T> = ::std::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
+    CannotDiscoverContracts {
+        network: String,
+        version: String,
+    },
+    ChainIdMismatch {
+        network: String,
+        expected: u64,
+        found: u64,
+    },
     CtrlcSetupError(ctrlc::Error),
     EmissionFundsV1ContractDoesNotExist,
     FailedToBuildEmail(failure::Error),
     FailedToBuildRequest(reqwest::Error),
     FailedToBuildTls(native_tls::Error),
+    FailedToCreateStateDir(std::io::Error),
+    FailedToParseBallotCanceledLog(String),
     FailedToParseBallotCreatedLog(String),
+    FailedToParseBallotFinalizedLog(String),
     FailedToParseRawLogToLog(ethabi::Error),
+    FailedToParseVoteLog(String),
+    FailedToPostToWebhook(reqwest::Error),
+    FailedToReadCheckpoint(std::io::Error),
+    FailedToReadConfigFile(std::io::Error),
+    FailedToReadDigest(std::io::Error),
+    FailedToReadEmailTemplate(String, std::io::Error),
+    FailedToReadLedger(std::io::Error),
+    FailedToReadOutbox(std::io::Error),
+    FailedToRenderEmailTemplate(String),
     FailedToResolveSmtpHostDomain(lettre::smtp::error::Error),
     FailedToSendEmail(lettre::smtp::error::Error),
+    FailedToSendTelegramMessage(reqwest::Error),
+    FailedToSignWebhookPayload(openssl::error::ErrorStack),
+    FailedToWriteCheckpoint(std::io::Error),
+    FailedToWriteDigest(std::io::Error),
+    FailedToWriteLedger(std::io::Error),
+    FailedToWriteOutbox(std::io::Error),
     InvalidAbi(String),
     InvalidBlockTime(String),
+    InvalidChainId(String),
+    InvalidCheckpoint(String),
+    InvalidConfigFile(String),
     InvalidContractAddr(String),
+    InvalidDigestEntry(String),
+    InvalidEmailDigest(String),
+    InvalidEmailMaxAttempts(String),
+    InvalidEmailTemplate(String),
+    InvalidLedgerEntry(String),
+    InvalidNetworkName(String),
     InvalidNotificationLimit(String),
+    InvalidOutboxEntry(String),
+    InvalidOutputFormat(String),
+    InvalidPruneAge(String),
+    InvalidReminderOffset(String),
+    InvalidSmtpAuthMechanism(String),
     InvalidSmtpPort(String),
+    InvalidSmtpSecurity(String),
     InvalidStartBlock(String),
     InvalidTail(String),
+    InvalidVotingKey(String),
+    InvalidWatchedAddress(String),
+    InvalidWebhookMaxRetries(String),
+    InvalidWebhookResponse(u16),
     JsonRpcResponseFailure(jsonrpc_core::types::response::Failure),
     MissingAbiFile(String),
+    MissingCheckpoint(String),
     MissingEnvVar(String),
     MustSpecifyAtLeastOneCliArgument(String),
     MustSpecifyOneCliArgument(String),
     MustSpecifyZeroOrOneCliArguments(String),
+    NoContractsToMonitor(String),
+    NotificationNotDelivered {
+        sink: String,
+        n_failed: usize,
+    },
     RequestFailed(reqwest::Error),
     StartBlockExceedsLastBlockMined {
         start_block: u64,
         last_mined_block: u64,
     },
+    StartBlockRequiresOneNetwork,
+    TelegramApiError(String),
+    UnknownSubscriptionRecipient(String),
     V1ContractsWereNotDeployedToXDaiChain,
 }
@@ -0,0 +1,160 @@
+use crate::notify::Notification;
+
+/// Escapes the characters that have a special meaning in HTML.
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns `true` if `s` is a "0x"-prefixed 20 byte hex string (i.e. how addresses are formatted
+/// in the notification's email text).
+fn is_address(s: &str) -> bool {
+    s.len() == 42 && s.starts_with("0x") && s[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn link(url: &str, text: &str) -> String {
+    format!("<a href=\"{}\">{}</a>", escape_html(url), escape_html(text))
+}
+
+fn row(field: &str, value_html: &str) -> String {
+    format!(
+        "<tr><th align=\"left\" valign=\"top\">{}</th><td>{}</td></tr>\n",
+        escape_html(field),
+        value_html,
+    )
+}
+
+/// Wraps the HTML sections of an email's HTML part into an HTML document.
+pub fn wrap_html(sections: &[String]) -> String {
+    format!("<html>\n<body>\n{}</body>\n</html>\n", sections.join("<hr>\n"))
+}
+
+/// Formats each line of an alert (e.g. a watch-list alert) as a bold paragraph.
+fn format_alert_html(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| format!("<p><strong>{}</strong></p>\n", escape_html(line)))
+        .collect()
+}
+
+/// Formats the HTML part of a notification email (see `format_html_section`), the alert lines are
+/// shown above the notification.
+pub fn format_html(notif: &Notification, body: &str, alert_lines: &[String]) -> String {
+    let section = format!(
+        "{}{}",
+        format_alert_html(alert_lines),
+        format_html_section(notif, body),
+    );
+    wrap_html(&[section])
+}
+
+/// Formats a notification as a heading followed by a table. Each "<field>: <value>" line of the
+/// email's plain-text body (as rendered by the body template) is rendered as a table row, other
+/// lines span both columns. Addresses and the block number are linked to the network's block
+/// explorer, and the ballot is linked to the governance DApp (when the network has an explorer or
+/// ballot URL configured).
+pub fn format_html_section(notif: &Notification, body: &str) -> String {
+    let config = notif.config();
+    let explorer_url = config.explorer_url.as_ref().map(|url| url.trim_right_matches('/'));
+    let mut rows = String::new();
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        let (field, value) = match line.find(": ") {
+            Some(i) => (&line[..i], line[i + 2..].trim()),
+            None => {
+                let line_html = escape_html(line);
+                rows.push_str(&format!("<tr><td colspan=\"2\">{}</td></tr>\n", line_html));
+                continue;
+            }
+        };
+        let value_html = match explorer_url {
+            Some(explorer_url) if is_address(value) => {
+                link(&format!("{}/address/{}", explorer_url, value), value)
+            }
+            Some(explorer_url) if field == "Block Number" => {
+                link(&format!("{}/blocks/{}", explorer_url, value), value)
+            }
+            _ => escape_html(value),
+        };
+        rows.push_str(&row(field, &value_html));
+        if field == "Contract" {
+            let addr = format!("{:?}", notif.contract().addr);
+            let addr_html = match explorer_url {
+                Some(explorer_url) => link(&format!("{}/address/{}", explorer_url, addr), &addr),
+                None => escape_html(&addr),
+            };
+            rows.push_str(&row("Contract Address", &addr_html));
+        }
+    }
+    let ballot_link = match config.ballot_url {
+        Some(ref ballot_url) => {
+            let url = ballot_url
+                .replace("{ballot_id}", &notif.ballot_id().to_string())
+                .replace("{contract}", &format!("{:?}", notif.contract().addr));
+            format!("<p>{}</p>\n", link(&url, "View this ballot in the governance DApp"))
+        }
+        None => String::new(),
+    };
+    format!(
+        "<h3>POA Network Governance Notification: {}</h3>\n\
+         <table cellpadding=\"4\">\n{}</table>\n{}",
+        escape_html(notif.event()),
+        rows,
+        ballot_link,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_html, format_html};
+    use crate::tests::{sokol_network_config, threshold_ballot_created};
+
+    #[test]
+    fn test_format_html() {
+        let mut config = sokol_network_config();
+        let notif = threshold_ballot_created(&config);
+        let html = format_html(&notif, &notif.email_text(), &[]);
+        assert!(html.contains("Memo</th><td>lower the threshold</td>"));
+        assert!(!html.contains("<a href"));
+
+        config.explorer_url = Some("https://blockscout.com/poa/sokol/".to_string());
+        config.ballot_url = Some("https://voting.example.com/ballots/{ballot_id}".to_string());
+        let notif = threshold_ballot_created(&config);
+        let alert_lines = ["Watch-List Alert: <test>".to_string()];
+        let html = format_html(&notif, &notif.email_text(), &alert_lines);
+        assert!(html.contains("<p><strong>Watch-List Alert: &lt;test&gt;</strong></p>"));
+        let contract_link = format!(
+            "<a href=\"https://blockscout.com/poa/sokol/address/{:?}\">",
+            notif.contract().addr,
+        );
+        assert!(html.contains(&contract_link));
+        let block_link = format!(
+            "<a href=\"https://blockscout.com/poa/sokol/blocks/{}\">",
+            notif.block_number(),
+        );
+        assert!(html.contains(&block_link));
+        assert!(html.contains("<a href=\"https://voting.example.com/ballots/12\">"));
+
+        assert_eq!(escape_html("<b>\"A&B\"</b>"), "&lt;b&gt;&quot;A&amp;B&quot;&lt;/b&gt;");
+    }
+
+    #[test]
+    fn test_format_html_from_template_body() {
+        let config = sokol_network_config();
+        let notif = threshold_ballot_created(&config);
+        let body = "Threshold ballot #12 on Sokol\nMemo: lower the threshold\n";
+        let html = format_html(&notif, body, &[]);
+        assert!(html.contains("<tr><td colspan=\"2\">Threshold ballot #12 on Sokol</td></tr>"));
+        assert!(html.contains("Memo</th><td>lower the threshold</td>"));
+        assert!(!html.contains("Ballot Type"));
+    }
+}
@@ -5,11 +5,12 @@ use std::path::Path;
 use chrono::{DateTime, TimeZone as _TimeZone, Utc};
 use slog::{info, o, warn, Drain as _Drain};
 use slog_term::{FullFormat, PlainSyncDecorator};
-use web3::types::BlockNumber;
 
-use crate::config::Config;
+use crate::chat::redact_webhook_url;
+use crate::config::{Config, Network, PoaContract};
 use crate::error::Error;
 use crate::notify::Notification;
+use crate::watch::WatchListMatch;
 
 // The date format used to name log files; e.g. "Oct-08-2018-14:09:00".
 const FILE_NAME_DATE_FORMAT: &str = "%b-%d-%Y-%H:%M:%S";
@@ -64,6 +65,40 @@ fn get_file_size_in_bytes(path: &str) -> usize {
         .len() as usize
 }
 
+/// Formats `e` for the logs. A `reqwest` error's text contains the request's URL, and both webhook
+/// URLs and Telegram Bot API URLs contain a secret token, so only the request error's HTTP status
+/// or underlying cause is logged.
+fn error_text(e: &Error) -> String {
+    match e {
+        Error::FailedToPostToWebhook(e) => {
+            format!("FailedToPostToWebhook({})", request_error_text(e))
+        }
+        Error::FailedToSendTelegramMessage(e) => {
+            format!("FailedToSendTelegramMessage({})", request_error_text(e))
+        }
+        e => format!("{:?}", e),
+    }
+}
+
+fn request_error_text(e: &reqwest::Error) -> String {
+    if let Some(status) = e.status() {
+        return format!("HTTP status {}", status.as_u16());
+    }
+    let kind = if e.is_http() {
+        "http error"
+    } else if e.is_redirect() {
+        "redirect error"
+    } else if e.is_serialization() {
+        "serialization error"
+    } else {
+        "request error"
+    };
+    match e.get_ref() {
+        Some(cause) => format!("{}: {}", kind, cause),
+        None => kind.to_string(),
+    }
+}
+
 enum LogLocation {
     Stderr,
     File(File),
@@ -125,24 +160,30 @@ pub struct Logger {
 
 impl Logger {
     pub fn new(config: &Config) -> Self {
-        let (logger, log_files) = if config.log_to_file {
-            create_logs_dir();
-            let mut log_files = read_logs_dir();
-            let current_log_file = rotate_log_files(&mut log_files);
-            let logger = create_slog_logger(LogLocation::File(current_log_file));
-            (logger, log_files)
-        } else {
-            let logger = create_slog_logger(LogLocation::Stderr);
-            (logger, vec![])
-        };
+        if !config.log_to_file {
+            return Logger::stderr();
+        }
+        create_logs_dir();
+        let mut log_files = read_logs_dir();
+        let current_log_file = rotate_log_files(&mut log_files);
         Logger {
-            logger,
+            logger: create_slog_logger(LogLocation::File(current_log_file)),
             log_files,
             log_count: 0,
             check_file_size_at: INITIAL_CHECK_FILE_SIZE_AT,
         }
     }
 
+    /// Creates a logger that writes to stderr.
+    pub fn stderr() -> Self {
+        Logger {
+            logger: create_slog_logger(LogLocation::Stderr),
+            log_files: vec![],
+            log_count: 0,
+            check_file_size_at: INITIAL_CHECK_FILE_SIZE_AT,
+        }
+    }
+
     fn logging_to_file(&self) -> bool {
         !self.log_files.is_empty()
     }
@@ -199,6 +240,31 @@ impl Logger {
         self.increment_log_count();
     }
 
+    pub fn log_no_webhook_urls_configured(&mut self) {
+        warn!(
+            &self.logger,
+            "webhook notifications are enabled, but there are no webhook urls configured"
+        );
+        self.increment_log_count();
+    }
+
+    pub fn log_no_telegram_chats_configured(&mut self) {
+        warn!(
+            &self.logger,
+            "telegram notifications are enabled, but there are no telegram chat ids configured"
+        );
+        self.increment_log_count();
+    }
+
+    pub fn log_no_chat_webhooks_configured(&mut self, network: &Network) {
+        warn!(
+            &self.logger,
+            "chat notifications are enabled, but there are no chat webhooks for this network";
+            "network" => network.to_string()
+        );
+        self.increment_log_count();
+    }
+
     pub fn log_notification_email_body(&mut self, notif: &Notification) {
         info!(
             &self.logger,
@@ -209,22 +275,105 @@ impl Logger {
     }
 
     pub fn log_notification(&mut self, notif: &Notification) {
-        let ballot_created_log = notif.log();
         info!(
             &self.logger,
             "governance notification";
-            "ballot" => format!("{:?}", ballot_created_log.ballot_type),
-            "ballot_id" => format!("{}", ballot_created_log.ballot_id),
-            "block_number" => format!("{}", ballot_created_log.block_number)
+            "network" => notif.network().to_string(),
+            "event" => notif.event(),
+            "ballot" => format!("{:?}", notif.ballot_type()),
+            "ballot_id" => format!("{}", notif.ballot_id()),
+            "block_number" => format!("{}", notif.block_number())
         );
         self.increment_log_count();
     }
 
+    pub fn log_watch_list_alert(&mut self, notif: &Notification, alert: &WatchListMatch) {
+        warn!(
+            &self.logger,
+            "watch-list alert, ballot contains a watched address";
+            "network" => notif.network().to_string(),
+            "ballot_id" => format!("{}", notif.ballot_id()),
+            "address" => format!("{:?}", alert.watched.addr),
+            "label" => alert.watched.label.clone().unwrap_or_default(),
+            "field" => alert.field
+        );
+        self.increment_log_count();
+    }
+
+    pub fn log_notification_already_sent(&mut self, notif: &Notification) {
+        info!(
+            &self.logger,
+            "skipping governance notification, notification was already sent";
+            "network" => notif.network().to_string(),
+            "event" => notif.event(),
+            "ballot" => format!("{:?}", notif.ballot_type()),
+            "ballot_id" => format!("{}", notif.ballot_id()),
+            "block_number" => format!("{}", notif.block_number())
+        );
+        self.increment_log_count();
+    }
+
+    pub fn log_failed_to_update_ledger(&mut self, e: Error) {
+        warn!(&self.logger, "failed to update notification ledger"; "error" => format!("{:?}", e));
+        self.increment_log_count();
+    }
+
     pub fn log_failed_to_build_email(&mut self, e: Error) {
         warn!(&self.logger, "failed to build email"; "error" => format!("{:?}", e));
         self.increment_log_count();
     }
 
+    pub fn log_failed_to_queue_digest_entry(&mut self, e: Error) {
+        warn!(&self.logger, "failed to queue email for the digest"; "error" => format!("{:?}", e));
+        self.increment_log_count();
+    }
+
+    pub fn log_failed_to_update_digest(&mut self, e: Error) {
+        warn!(&self.logger, "failed to update the digest file"; "error" => format!("{:?}", e));
+        self.increment_log_count();
+    }
+
+    pub fn log_digest_email_sent(&mut self, recipient: &str, n_entries: usize) {
+        info!(
+            &self.logger,
+            "digest email sent";
+            "to" => recipient,
+            "notifications" => n_entries
+        );
+        self.increment_log_count();
+    }
+
+    pub fn log_email_queued_for_retry(
+        &mut self,
+        recipient: &str,
+        attempts: u32,
+        next_attempt_at: DateTime<Utc>,
+    ) {
+        info!(
+            &self.logger,
+            "email added to the outbox, will retry";
+            "recipient" => recipient,
+            "attempts" => attempts,
+            "next_attempt_at" => next_attempt_at.to_string()
+        );
+        self.increment_log_count();
+    }
+
+    pub fn log_email_dead_lettered(&mut self, recipient: &str, attempts: u32) {
+        warn!(
+            &self.logger,
+            "giving up on email, moved to the outbox's dead-letter file";
+            "recipient" => recipient,
+            "attempts" => attempts
+        );
+        self.increment_log_count();
+    }
+
+    pub fn log_failed_to_update_outbox(&mut self, e: Error) {
+        warn!(&self.logger, "failed to update the outbox"; "error" => format!("{:?}", e));
+        self.increment_log_count();
+    }
+
     pub fn log_failed_to_send_email(&mut self, recipient: &str, e: Error) {
         warn!(
             &self.logger,
@@ -240,6 +389,77 @@ impl Logger {
         self.increment_log_count();
     }
 
+    pub fn log_failed_to_post_to_webhook(&mut self, url: &str, e: Error) {
+        warn!(
+            &self.logger,
+            "failed to post to webhook";
+            "webhook" => redact_webhook_url(url),
+            "error" => error_text(&e)
+        );
+        self.increment_log_count();
+    }
+
+    pub fn log_webhook_post_queued_for_retry(&mut self, url: &str, next_attempt_at: DateTime<Utc>) {
+        info!(
+            &self.logger,
+            "webhook post queued, will retry";
+            "webhook" => redact_webhook_url(url),
+            "next_attempt_at" => next_attempt_at.to_string()
+        );
+        self.increment_log_count();
+    }
+
+    pub fn log_gave_up_on_webhook_post(&mut self, url: &str) {
+        warn!(
+            &self.logger,
+            "giving up on webhook post, reached max retries";
+            "webhook" => redact_webhook_url(url)
+        );
+        self.increment_log_count();
+    }
+
+    pub fn log_posted_to_webhook(&mut self, url: &str) {
+        info!(&self.logger, "posted to webhook"; "webhook" => redact_webhook_url(url));
+        self.increment_log_count();
+    }
+
+    pub fn log_failed_to_send_telegram_message(&mut self, chat_id: &str, e: Error) {
+        warn!(
+            &self.logger,
+            "failed to send telegram message";
+            "chat_id" => chat_id,
+            "error" => error_text(&e)
+        );
+        self.increment_log_count();
+    }
+
+    pub fn log_telegram_message_sent(&mut self, chat_id: &str) {
+        info!(&self.logger, "telegram message sent"; "chat_id" => chat_id);
+        self.increment_log_count();
+    }
+
+    pub fn log_recipient_already_voted(&mut self, recipient: &str) {
+        info!(&self.logger, "skipping reminder, recipient has already voted"; "to" => recipient);
+        self.increment_log_count();
+    }
+
+    pub fn log_recipient_not_subscribed(&mut self, recipient: &str) {
+        info!(&self.logger, "skipping email, recipient is not subscribed"; "to" => recipient);
+        self.increment_log_count();
+    }
+
+    pub fn log_discovered_contract(&mut self, network: &Network, contract: &PoaContract) {
+        info!(
+            &self.logger,
+            "discovered contract address";
+            "network" => network.to_string(),
+            "contract" => format!("{:?}", contract.kind),
+            "version" => format!("{:?}", contract.version),
+            "address" => format!("0x{}", hex::encode(contract.addr.0))
+        );
+        self.increment_log_count();
+    }
+
     pub fn log_reached_notification_limit(&mut self, notification_limit: usize) {
         warn!(
             &self.logger,
@@ -249,9 +469,50 @@ impl Logger {
         self.increment_log_count();
     }
 
-    pub fn log_finished_block_window(&mut self, start: BlockNumber, stop: BlockNumber) {
-        let block_range = format!("{:?}...{:?}", start, stop);
-        info!(&self.logger, "finished checking blocks"; "block_range" => block_range);
+    pub fn log_holding_checkpoint(&mut self, network: &Network, block: u64) {
+        warn!(
+            &self.logger,
+            "a notification failed to send, the checkpoint will stay before this block until \
+             poagov restarts";
+            "network" => network.to_string(),
+            "block" => block
+        );
         self.increment_log_count();
     }
+
+    pub fn log_finished_block_window(&mut self, network: &Network, start: u64, stop: u64) {
+        let block_range = format!("{}...{}", start, stop);
+        info!(
+            &self.logger,
+            "finished checking blocks";
+            "network" => network.to_string(),
+            "block_range" => block_range
+        );
+        self.increment_log_count();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::error_text;
+    use crate::error::Error;
+
+    #[test]
+    fn test_webhook_error_text_omits_url() {
+        // Nothing listens on port 1, so the request fails before a response is received.
+        let url = "http://127.0.0.1:1/hooks/secret-token";
+        let e = reqwest::Client::new().post(url).send().unwrap_err();
+        let text = error_text(&Error::FailedToPostToWebhook(e));
+        assert!(text.starts_with("FailedToPostToWebhook("));
+        assert!(!text.contains("secret-token"));
+    }
+
+    #[test]
+    fn test_telegram_error_text_omits_bot_token() {
+        let url = "http://127.0.0.1:1/bot123456:secret-bot-token/sendMessage";
+        let e = reqwest::Client::new().post(url).send().unwrap_err();
+        let text = error_text(&Error::FailedToSendTelegramMessage(e));
+        assert!(text.starts_with("FailedToSendTelegramMessage("));
+        assert!(!text.contains("secret-bot-token"));
+    }
 }
@@ -1,24 +1,49 @@
 mod blockchain;
+mod chat;
 mod cli;
 mod client;
 mod config;
+mod config_file;
+mod digest;
+mod discovery;
+mod email;
 mod error;
+mod html;
 mod logger;
 mod notify;
+mod reminders;
 mod response;
+mod sink;
+mod state;
+mod telegram;
+mod templates;
+mod votes;
+mod watch;
+mod webhook;
 
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use chrono::{Duration, Utc};
 use lazy_static::lazy_static;
+use web3::types::U256;
 
 use crate::blockchain::BlockchainIter;
-use crate::cli::parse_cli;
+use crate::cli::{parse_cli, Cli};
 use crate::client::RpcClient;
-use crate::config::{Config, ContractVersion};
+use crate::config::{
+    self, Config, ContractType, ContractVersion, NetworkConfig, PoaContract, VoteNotifications,
+};
+use crate::config_file::ConfigFile;
+use crate::discovery::{ContractDiscovery, ProxyUpgrade};
 use crate::error::{Error, Result};
 use crate::logger::Logger;
 use crate::notify::{Notification, Notifier};
+use crate::reminders::{Reminder, ReminderScheduler};
+use crate::response::common::{BallotState, VoteLog};
+use crate::state::checkpoint::Checkpoint;
+use crate::state::ledger::NotificationLedger;
+use crate::votes::VoteTally;
 
 lazy_static! {
     // Tracks whether or not the environment variables have been loaded from the .env file.
@@ -58,84 +83,502 @@ fn set_ctrlc_handler(logger: Arc<Mutex<Logger>>) -> Result<Arc<AtomicBool>> {
     }
 }
 
-fn main() -> Result<()> {
-    load_env_file();
+/// Handles the `--prune-notified` and `--list-notified` CLI arguments. Neither argument requires
+/// a connection to a blockchain, so they are handled before the `Config` is built.
+fn manage_notification_ledger(cli: &Cli) -> Result<()> {
+    let file = ConfigFile::from_cli(cli)?;
+    let state_dir = config::state_dir(cli, file.as_ref());
+    let mut ledger = NotificationLedger::open(&state_dir)?;
+    if let Some(n_days_str) = cli.prune_notified() {
+        let n_days: i64 = n_days_str
+            .parse()
+            .map_err(|_| Error::InvalidPruneAge(n_days_str.to_string()))?;
+        let n_pruned = ledger.prune(Utc::now() - Duration::days(n_days))?;
+        println!("removed {} entries from the notification ledger", n_pruned);
+    }
+    if cli.list_notified() {
+        for entry in ledger.entries() {
+            println!("{}", entry);
+        }
+    }
+    Ok(())
+}
 
-    let cli = parse_cli();
-    let config = Config::new(&cli)?;
-    let logger = Arc::new(Mutex::new(Logger::new(&config)));
-    let running = set_ctrlc_handler(logger.clone())?;
-    let client = RpcClient::new(config.endpoint.clone());
-    let blockchain_iter = BlockchainIter::new(&client, &config, running)?;
-    let mut notifier = Notifier::new(&config, logger.clone())?;
+/// Converts the `Vote` logs found for a single contract within a block-window into
+/// `Notification`s. When `--votes` is set, each vote gets its own notification; when
+/// `--vote-digest` is set, the votes are grouped into one notification per ballot.
+fn get_vote_notifications<'a>(
+    config: &Config,
+    network_config: &'a NetworkConfig,
+    client: &RpcClient,
+    contract: &'a PoaContract,
+    vote_logs: Vec<VoteLog>,
+) -> Result<Vec<Notification<'a>>> {
+    let mut notifications = vec![];
+    match config.vote_notifications {
+        VoteNotifications::Disabled => {}
+        VoteNotifications::PerVote => {
+            // Read each ballot's state once per block-window, every vote on a ballot within the
+            // window shows the same (current) tally.
+            let mut ballot_states: Vec<(U256, BallotState)> = vec![];
+            for log in vote_logs {
+                let pos = ballot_states.iter().position(|(id, _)| *id == log.ballot_id);
+                let ballot_state = match pos {
+                    Some(i) => ballot_states[i].1.clone(),
+                    None => {
+                        let ballot_state = client.get_ballot_state(contract, log.ballot_id)?;
+                        ballot_states.push((log.ballot_id, ballot_state.clone()));
+                        ballot_state
+                    }
+                };
+                let tally = VoteTally::from_ballot_state(&ballot_state);
+                let notification =
+                    Notification::from_vote(network_config, contract, log, ballot_state, tally);
+                notifications.push(notification);
+            }
+        }
+        VoteNotifications::Digest => {
+            // Group the votes by ballot, keeping the ballots in the order that they were first
+            // voted on.
+            let mut votes_by_ballot: Vec<Vec<VoteLog>> = vec![];
+            for log in vote_logs {
+                let pos = votes_by_ballot
+                    .iter()
+                    .position(|logs| logs[0].ballot_id == log.ballot_id);
+                match pos {
+                    Some(i) => votes_by_ballot[i].push(log),
+                    None => votes_by_ballot.push(vec![log]),
+                };
+            }
+            for logs in votes_by_ballot {
+                let ballot_state = client.get_ballot_state(contract, logs[0].ballot_id)?;
+                let tally = VoteTally::from_ballot_state(&ballot_state);
+                let notification = Notification::from_vote_digest(
+                    network_config,
+                    contract,
+                    logs,
+                    ballot_state,
+                    tally,
+                );
+                notifications.push(notification);
+            }
+        }
+    };
+    Ok(notifications)
+}
 
-    // If email notifications have been enabled but there are no email recipients configured, warn
-    // the user.
-    if config.email_notifications && config.email_recipients.is_empty() {
-        logger.lock().unwrap().log_no_email_recipients_configured();
+/// Starts tracking reminders for the ballots that are already open when `poagov` starts, i.e.
+/// ballots created before the start block (or before the checkpoint when using `--resume`).
+/// Reminders that were sent before a restart are not resent, they are found in the notification
+/// ledger.
+fn track_open_ballots(
+    client: &RpcClient,
+    contracts: &[PoaContract],
+    reminders: &mut ReminderScheduler,
+) -> Result<()> {
+    if !reminders.is_enabled() {
+        return Ok(());
     }
-    logger.lock().unwrap().log_starting_poagov();
+    for contract in contracts.iter() {
+        for ballot_id in client.get_open_ballot_ids(contract)? {
+            let ballot_state = client.get_ballot_state(contract, ballot_id)?;
+            if !ballot_state.is_closed() {
+                reminders.track(contract.addr, ballot_id, &ballot_state);
+            }
+        }
+    }
+    Ok(())
+}
 
-    'blockchain_walker: for block_range_res in blockchain_iter {
+/// Notifies the governance notification recipients. The `Notifier` is shared by every network's
+/// thread, so the notification limit is shared across networks. Sets `all_delivered` to `false`
+/// if a notification failed to send. Returns `true` if the notification limit has been reached,
+/// in which case `poagov` begins to shut down.
+fn send_notifications(
+    config: &Config,
+    notifications: Vec<Notification>,
+    notifier: &Mutex<Notifier>,
+    logger: &Mutex<Logger>,
+    running: &AtomicBool,
+    all_delivered: &mut bool,
+) -> bool {
+    for notification in notifications {
+        let mut notifier = notifier.lock().unwrap();
+        if notifier.reached_limit() {
+            running.store(false, Ordering::SeqCst);
+            return true;
+        }
+        if !notifier.notify(&notification) {
+            *all_delivered = false;
+        }
+        if notifier.reached_limit() {
+            let limit = config.notification_limit.unwrap();
+            logger.lock().unwrap().log_reached_notification_limit(limit);
+            running.store(false, Ordering::SeqCst);
+            return true;
+        }
+    }
+    false
+}
+
+/// Monitors a single network for governance events until `poagov` is shut down (either by ctrl-c,
+/// by reaching the notification limit, or by an error occurring in any network's thread).
+fn monitor_network(
+    config: &Config,
+    network_config: &NetworkConfig,
+    notifier: &Mutex<Notifier>,
+    logger: Arc<Mutex<Logger>>,
+    running: Arc<AtomicBool>,
+) -> Result<()> {
+    let client = RpcClient::new(network_config.endpoint.clone());
+    if let Some(expected) = network_config.chain_id {
+        let found = client.get_chain_id()?;
+        if found != expected {
+            let network = network_config.network.to_string();
+            return Err(Error::ChainIdMismatch { network, expected, found });
+        }
+    }
+    // When `--discover-contracts` is set, the contracts' addresses are read from `ProxyStorage`
+    // before monitoring starts and are periodically refreshed while `poagov` runs.
+    let mut contracts = network_config.contracts.clone();
+    let mut proxy_contracts = network_config.proxy_contracts.clone();
+    let mut discovery = ContractDiscovery::new(&client, network_config, logger.clone());
+    discovery.update(&mut contracts, &mut proxy_contracts)?;
+    let blockchain_iter = BlockchainIter::new(&client, network_config, running.clone())?;
+    let checkpoint = Checkpoint::new(&config.state_dir, &network_config.network);
+    let mut reminders = ReminderScheduler::new(config);
+    track_open_ballots(&client, &contracts, &mut reminders)?;
+    // Set once a notification fails to send, from then on the checkpoint is no longer advanced.
+    let mut all_delivered = true;
+
+    for block_range_res in blockchain_iter {
         let (start_block, stop_block) = block_range_res?;
+        let was_all_delivered = all_delivered;
+        discovery.update(&mut contracts, &mut proxy_contracts)?;
         let mut notifications = vec![];
+        // Each accepted proxy ballot, paired with the Proxy contract that finalized it. The Proxy
+        // contract is recorded when its log is read, as a proxy ballot may replace the Proxy
+        // contract itself.
+        let mut proxy_upgrades: Vec<(PoaContract, ProxyUpgrade)> = vec![];
 
-        // For each contract that we are monitoring for governance events, get the ballot-created
-        // events that fall within the current `BlockchainIter`'s block window, convert those
-        // ballot-created logs to `Notification`s.
-        for contract in config.contracts.iter() {
+        // For each contract that we are monitoring for governance events, get the ballot-created,
+        // vote, and ballot-finalized events that fall within the current `BlockchainIter`'s block
+        // window, convert those logs to `Notification`s.
+        for contract in contracts.iter() {
             let ballot_created_logs = client.get_ballot_created_logs(
                 contract,
-                start_block,
-                stop_block,
+                start_block.into(),
+                stop_block.into(),
             )?;
             for log in ballot_created_logs {
                 let notification = match contract.version {
                     ContractVersion::V1 => {
                         let voting_state = client.get_voting_state(contract, log.ballot_id)?;
-                        Notification::from_voting_state(&config, log, voting_state)
+                        let ballot_state = voting_state.clone().into();
+                        reminders.track(contract.addr, log.ballot_id, &ballot_state);
+                        Notification::from_voting_state(network_config, contract, log, voting_state)
                     }
                     ContractVersion::V2 => {
-                        let ballot_info = client.get_ballot_info(contract, log.ballot_id)?;
-                        Notification::from_ballot_info(&config, log, ballot_info)
+                        let ballot_info = client.get_ballot_info(contract, log.ballot_id, None)?;
+                        let ballot_state = ballot_info.clone().into();
+                        reminders.track(contract.addr, log.ballot_id, &ballot_state);
+                        Notification::from_ballot_info(network_config, contract, log, ballot_info)
                     }
                 };
                 notifications.push(notification);
             }
+
+            // Votes are only requested from the blockchain if vote notifications have been enabled.
+            if config.vote_notifications != VoteNotifications::Disabled {
+                let vote_logs =
+                    client.get_vote_logs(contract, start_block.into(), stop_block.into())?;
+                let vote_notifications = get_vote_notifications(
+                    config,
+                    network_config,
+                    &client,
+                    contract,
+                    vote_logs,
+                )?;
+                notifications.extend(vote_notifications);
+            }
+
+            // Only the V2 Emission Funds contract emits `BallotCanceled` events. Once a ballot has
+            // been canceled it can no longer be voted on.
+            if contract.kind == ContractType::Emission && contract.version == ContractVersion::V2 {
+                let ballot_canceled_logs = client.get_ballot_canceled_logs(
+                    contract,
+                    start_block.into(),
+                    stop_block.into(),
+                )?;
+                for log in ballot_canceled_logs {
+                    reminders.untrack(contract.addr, log.ballot_id);
+                    let ballot_info = client.get_ballot_info(contract, log.ballot_id, None)?;
+                    let notification = Notification::from_canceled_ballot_info(
+                        network_config,
+                        contract,
+                        log,
+                        ballot_info,
+                    );
+                    notifications.push(notification);
+                }
+            }
+
+            // When a ballot is finalized, we re-query the contract for the ballot's final state
+            // to determine whether or not the ballot was accepted.
+            let ballot_finalized_logs = client.get_ballot_finalized_logs(
+                contract,
+                start_block.into(),
+                stop_block.into(),
+            )?;
+            for log in ballot_finalized_logs {
+                // A finalized ballot can no longer be voted on, stop tracking its reminders.
+                reminders.untrack(contract.addr, log.ballot_id);
+                let notification = match contract.version {
+                    ContractVersion::V1 => {
+                        let voting_state = client.get_voting_state(contract, log.ballot_id)?;
+                        let upgrade = ProxyUpgrade::from_voting_state(
+                            &voting_state,
+                            log.ballot_id,
+                            log.block_number,
+                        );
+                        proxy_upgrades.extend(upgrade.map(|upgrade| (contract.clone(), upgrade)));
+                        Notification::from_finalized_voting_state(
+                            network_config,
+                            contract,
+                            log,
+                            voting_state,
+                        )
+                    }
+                    ContractVersion::V2 => {
+                        let ballot_info = client.get_ballot_info(contract, log.ballot_id, None)?;
+                        let quorum_state = client.get_quorum_state(contract, log.ballot_id)?;
+                        let upgrade = ProxyUpgrade::from_ballot_info(
+                            &ballot_info,
+                            quorum_state,
+                            log.ballot_id,
+                            log.block_number,
+                        );
+                        proxy_upgrades.extend(upgrade.map(|upgrade| (contract.clone(), upgrade)));
+                        Notification::from_finalized_ballot_info(
+                            network_config,
+                            contract,
+                            log,
+                            ballot_info,
+                            quorum_state,
+                        )
+                    }
+                };
+                notifications.push(notification);
+            }
+
+            // Check each open ballot that may have a deadline reminder or finalize alert due.
+            if reminders.is_enabled() {
+                let now = Utc::now();
+                for ballot_id in reminders.due_ballots(contract.addr, now) {
+                    let ballot_state = client.get_ballot_state(contract, ballot_id)?;
+                    for reminder in reminders.check(contract.addr, ballot_id, &ballot_state, now) {
+                        // Deadline reminders are only sent to the recipients whose voting key has
+                        // not yet voted on the ballot.
+                        let mut voted = vec![];
+                        if let Reminder::Deadline(_) = reminder {
+                            for recipient in config.email_recipients.iter() {
+                                if let Some(voting_key) = recipient.voting_key {
+                                    if client.has_already_voted(contract, ballot_id, voting_key)? {
+                                        voted.push(voting_key);
+                                    }
+                                }
+                            }
+                        }
+                        let notification = Notification::from_reminder(
+                            network_config,
+                            contract,
+                            ballot_id,
+                            stop_block.into(),
+                            ballot_state.clone(),
+                            reminder,
+                            voted,
+                        );
+                        notifications.push(notification);
+                    }
+                }
+            }
+        }
+
+        // The V1 Proxy contract's ballots are checked for upgrades to the monitored contracts even
+        // when the Proxy contract is not monitored.
+        for proxy_contract in proxy_contracts.iter() {
+            let ballot_finalized_logs = client.get_ballot_finalized_logs(
+                proxy_contract,
+                start_block.into(),
+                stop_block.into(),
+            )?;
+            for log in ballot_finalized_logs {
+                let voting_state = client.get_voting_state(proxy_contract, log.ballot_id)?;
+                let upgrade =
+                    ProxyUpgrade::from_voting_state(&voting_state, log.ballot_id, log.block_number);
+                proxy_upgrades.extend(upgrade.map(|upgrade| (proxy_contract.clone(), upgrade)));
+            }
         }
 
         // Sort the notifications by ascending block number.
         notifications.sort_unstable_by(|notif1, notif2| {
-            notif1.log().block_number.cmp(&notif2.log().block_number)
+            notif1.block_number().cmp(&notif2.block_number())
         });
 
-        // Notify the governance notifications recipients.
-        for notification in notifications {
-            notifier.notify(&notification);
-            if notifier.reached_limit() {
-                let limit = config.notification_limit.unwrap();
-                logger.lock().unwrap().log_reached_notification_limit(limit);
-                break 'blockchain_walker;
+        let reached_limit = send_notifications(
+            config,
+            notifications,
+            notifier,
+            &logger,
+            &running,
+            &mut all_delivered,
+        );
+        if reached_limit {
+            return Ok(());
+        }
+
+        // An accepted proxy ballot may have replaced one of the contracts that we are monitoring,
+        // in which case we monitor the new contract starting with the next block-window.
+        let mut address_changes = vec![];
+        for (proxy_contract, upgrade) in proxy_upgrades {
+            discovery.follow_proxy_upgrade(&mut proxy_contracts, &upgrade);
+            if let Some(change) = discovery.follow_proxy_upgrade(&mut contracts, &upgrade) {
+                address_changes.push((proxy_contract, change));
             }
         }
+        let notifications: Vec<Notification> = address_changes
+            .iter()
+            .map(|(proxy_contract, change)| {
+                Notification::from_contract_address_change(
+                    network_config,
+                    proxy_contract,
+                    change.clone(),
+                )
+            })
+            .collect();
+        let reached_limit = send_notifications(
+            config,
+            notifications,
+            notifier,
+            &logger,
+            &running,
+            &mut all_delivered,
+        );
+        if reached_limit {
+            return Ok(());
+        }
 
+        notifier.lock().unwrap().end_block_window();
         logger
             .lock()
             .unwrap()
-            .log_finished_block_window(start_block, stop_block);
+            .log_finished_block_window(&network_config.network, start_block, stop_block);
+
+        // Only checkpoint a block-window once all of its notifications have been sent, this way a
+        // `poagov` process started with `--resume` will never skip a block. Once a notification
+        // has failed to send, the checkpoint stays before the block-window that generated it, so
+        // that `--resume` retries it (the notifications that were sent are skipped using the
+        // notification ledger).
+        if all_delivered {
+            checkpoint.write(stop_block)?;
+        } else if was_all_delivered {
+            logger
+                .lock()
+                .unwrap()
+                .log_holding_checkpoint(&network_config.network, start_block);
+        }
     }
 
     Ok(())
 }
 
+fn main() -> Result<()> {
+    load_env_file();
+
+    let cli = parse_cli();
+    if cli.list_notified() || cli.prune_notified().is_some() {
+        return manage_notification_ledger(&cli);
+    }
+    let config = Config::new(&cli)?;
+    let logger = Arc::new(Mutex::new(Logger::new(&config)));
+    let running = set_ctrlc_handler(logger.clone())?;
+    let notifier = Mutex::new(Notifier::new(&config, logger.clone())?);
+
+    // If email notifications have been enabled but there are no email recipients configured, warn
+    // the user.
+    if config.email_notifications && config.email_recipients.is_empty() {
+        logger.lock().unwrap().log_no_email_recipients_configured();
+    }
+    if config.chat_notifications {
+        for network_config in config.networks.iter() {
+            if network_config.chat_webhooks.is_empty() {
+                logger
+                    .lock()
+                    .unwrap()
+                    .log_no_chat_webhooks_configured(&network_config.network);
+            }
+        }
+    }
+    if config.webhook_notifications && config.webhook_urls.is_empty() {
+        logger.lock().unwrap().log_no_webhook_urls_configured();
+    }
+    if config.telegram_notifications && config.telegram_chat_ids.is_empty() {
+        logger.lock().unwrap().log_no_telegram_chats_configured();
+    }
+    logger.lock().unwrap().log_starting_poagov();
+
+    // Each network is monitored in its own thread, every thread shares the same `Notifier` and
+    // `Logger`. If an error occurs in any thread, every other thread is shut down.
+    let results: Vec<Result<()>> = crossbeam_utils::thread::scope(|scope| {
+        let handles: Vec<_> = config
+            .networks
+            .iter()
+            .map(|network_config| {
+                let config = &config;
+                let notifier = &notifier;
+                let logger = logger.clone();
+                let running = running.clone();
+                scope.spawn(move |_| {
+                    let res =
+                        monitor_network(config, network_config, notifier, logger, running.clone());
+                    if res.is_err() {
+                        running.store(false, Ordering::SeqCst);
+                    }
+                    res
+                })
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    })
+    .unwrap();
+
+    // Send any notifications that are still waiting for the next email digest.
+    notifier.lock().unwrap().flush();
+    results.into_iter().collect()
+}
+
 #[cfg(test)]
 pub mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    use chrono::{Duration, Utc};
+    use web3::types::{Address, U256};
+
     use super::load_env_file;
-    use crate::config::{ContractType, ContractVersion, Network};
+    use crate::config::{
+        ContractType, ContractVersion, Network, NetworkConfig, PoaContract, StartBlock,
+    };
+    use crate::notify::Notification;
+    use crate::response::common::{BallotCreatedLog, BallotType};
+    use crate::response::v1::{self, ProxyVotingState, VotingState};
+    use crate::response::v2::ThresholdBallotInfo;
 
-    pub const CORE_NETWORK: Network = Network::Core;
-    pub const SOKOL_NETWORK: Network = Network::Sokol;
-    pub const XDAI_NETWORK: Network = Network::XDai;
+    pub const CORE_NETWORK: Network = Network::CORE;
+    pub const SOKOL_NETWORK: Network = Network::SOKOL;
+    pub const XDAI_NETWORK: Network = Network::XDAI;
 
     pub const V1_VERSION: ContractVersion = ContractVersion::V1;
     pub const V2_VERSION: ContractVersion = ContractVersion::V2;
@@ -157,4 +600,155 @@ pub mod tests {
     pub fn setup() {
         load_env_file();
     }
+
+    /// A Sokol `NetworkConfig` that monitors the V2 Threshold contract.
+    pub fn sokol_network_config() -> NetworkConfig {
+        setup();
+        let contract = PoaContract::read(ContractType::Threshold, &SOKOL_NETWORK, V2_VERSION)
+            .unwrap_or_else(|e| panic!("Failed to load contract: {:?}", e));
+        NetworkConfig {
+            network: SOKOL_NETWORK,
+            endpoint: "http://localhost:8545".to_string(),
+            chain_id: None,
+            contracts: vec![contract],
+            proxy_contracts: vec![],
+            proxy_storages: vec![],
+            discover_contracts: false,
+            start_block: StartBlock::Latest,
+            block_time: 5,
+            chat_webhooks: vec![],
+            explorer_url: None,
+            ballot_url: None,
+        }
+    }
+
+    /// A `BallotCreated` notification for a V2 Threshold ballot in `config`'s first contract.
+    pub fn threshold_ballot_created(config: &NetworkConfig) -> Notification {
+        let end_time = Utc::now() + Duration::days(2);
+        let ballot_info = ThresholdBallotInfo {
+            start_time: end_time - Duration::days(2),
+            end_time,
+            total_voters: U256::from(0),
+            progress: U256::from(0),
+            is_finalized: false,
+            proposed_value: U256::from(3),
+            creator: Address::zero(),
+            memo: "lower the threshold".to_string(),
+            can_be_finalized_now: false,
+            already_voted: false,
+        };
+        let log = BallotCreatedLog {
+            block_number: U256::from(6107511),
+            ballot_id: U256::from(12),
+            ballot_type: BallotType::Threshold,
+            creator: Address::zero(),
+        };
+        Notification::from_ballot_info(config, &config.contracts[0], log, ballot_info.into())
+    }
+
+    /// The final state of a V1 proxy ballot that proposed `proposed_value` as the new address of
+    /// the V1 Keys contract.
+    pub fn v1_keys_proxy_voting_state(
+        quorum_state: v1::QuorumState,
+        proposed_value: Address,
+    ) -> VotingState {
+        let end_time = Utc::now() - Duration::days(1);
+        ProxyVotingState {
+            start_time: end_time - Duration::days(2),
+            end_time,
+            total_voters: U256::from(3),
+            progress: U256::from(3),
+            is_finalized: true,
+            quorum_state,
+            index: U256::from(0),
+            min_threshold_of_voters: U256::from(2),
+            proposed_value,
+            contract_type: U256::from(2),
+            creator: Address::zero(),
+            memo: "upgrade the keys contract".to_string(),
+        }
+        .into()
+    }
+
+    /// Starts a local SMTP server that stands in for an unauthenticated, plain-text mail relay. The
+    /// server accepts a single email, then sends the SMTP session (each command received and the
+    /// email's data) into the returned channel. Returns the server's port.
+    pub fn stand_in_smtp_server() -> (u16, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            stream.write_all(b"220 localhost ESMTP\r\n").unwrap();
+            let mut session = String::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                session.push_str(&line);
+                let command = line.trim_end().to_uppercase();
+                line.clear();
+                if command.starts_with("DATA") {
+                    stream.write_all(b"354 end data with <CR><LF>.<CR><LF>\r\n").unwrap();
+                    while reader.read_line(&mut line).unwrap() > 0 && line != ".\r\n" {
+                        session.push_str(&line);
+                        line.clear();
+                    }
+                    line.clear();
+                    stream.write_all(b"250 OK\r\n").unwrap();
+                    tx.send(session.clone()).unwrap();
+                } else if command.starts_with("QUIT") {
+                    stream.write_all(b"221 bye\r\n").unwrap();
+                    break;
+                } else {
+                    stream.write_all(b"250 OK\r\n").unwrap();
+                }
+            }
+        });
+        (port, rx)
+    }
+
+    /// Starts a local HTTP server that stands in for a webhook or bot API. The server accepts a
+    /// single request, responds with `status` and `body`, then sends the raw request into the
+    /// returned channel. Returns the server's base URL.
+    pub fn stand_in_server(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![];
+            let mut buf = [0u8; 4096];
+            // Read until we have received the headers and the entire body.
+            loop {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(headers_end) = text.find("\r\n\r\n") {
+                    let content_length = text[..headers_end]
+                        .lines()
+                        .find(|line| line.to_lowercase().starts_with("content-length:"))
+                        .and_then(|line| line[15..].trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if request.len() >= headers_end + 4 + content_length {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            let resp = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body,
+            );
+            stream.write_all(resp.as_bytes()).unwrap();
+            tx.send(String::from_utf8(request).unwrap()).unwrap();
+        });
+        (url, rx)
+    }
 }
@@ -1,95 +1,500 @@
 use std::sync::{Arc, Mutex};
 
-use lettre::{SendableEmail, Transport as _Transport};
-use lettre::smtp::{ClientSecurity, ConnectionReuseParameters, SmtpClient, SmtpTransport};
-use lettre::smtp::authentication::{Credentials, Mechanism};
-use lettre::smtp::client::net::ClientTlsParameters;
-use lettre_email::{Email, EmailBuilder};
-use native_tls::TlsConnector;
-
-use crate::config::Config;
-use crate::error::{Error, Result};
+use serde_json as json;
+use web3::types::{Address, U256};
+
+use crate::config::{Config, Network, NetworkConfig, PoaContract, Recipient};
+use crate::discovery::ContractAddressChange;
+use crate::error::Result;
 use crate::logger::Logger;
-use crate::response::common::BallotCreatedLog;
+use crate::reminders::{format_offset, Reminder};
+use crate::response::common::{
+    BallotCanceledLog, BallotCreatedLog, BallotFinalizedLog, BallotState, BallotType, VoteLog,
+};
 use crate::response::v1::VotingState;
-use crate::response::v2::BallotInfo;
+use crate::response::v2::{BallotInfo, QuorumState};
+use crate::response::JSON_SCHEMA_VERSION;
+use crate::sink::{Sent, Sink};
+use crate::state::ledger::{LedgerKey, NotificationLedger};
+use crate::votes::VoteTally;
+use crate::watch::{find_matches, WatchedAddress};
 
 #[derive(Clone, Debug)]
 pub enum Notification<'a> {
     VotingState {
-        config: &'a Config,
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
         log: BallotCreatedLog,
         voting_state: VotingState,
     },
     BallotInfo {
-        config: &'a Config,
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
         log: BallotCreatedLog,
         ballot_info: BallotInfo,
     },
+    FinalizedVotingState {
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        log: BallotFinalizedLog,
+        voting_state: VotingState,
+    },
+    FinalizedBallotInfo {
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        log: BallotFinalizedLog,
+        ballot_info: BallotInfo,
+        quorum_state: QuorumState,
+    },
+    /// Only the V2 Emission Funds contract emits `BallotCanceled` events.
+    CanceledBallotInfo {
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        log: BallotCanceledLog,
+        ballot_info: BallotInfo,
+    },
+    /// A reminder generated for an open ballot by the `ReminderScheduler`.
+    Reminder {
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        ballot_id: U256,
+        // The last block of the block-window in which the reminder was generated.
+        block_number: U256,
+        ballot_state: BallotState,
+        reminder: Reminder,
+        // The voting keys (of the configured recipients) that have already voted on the ballot.
+        voted: Vec<Address>,
+    },
+    Vote {
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        log: VoteLog,
+        ballot_state: BallotState,
+        tally: VoteTally,
+    },
+    /// Contains every vote cast on a single ballot within a block-window.
+    VoteDigest {
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        logs: Vec<VoteLog>,
+        ballot_state: BallotState,
+        tally: VoteTally,
+    },
+    /// An accepted proxy ballot changed the address of a monitored contract, `contract` is the
+    /// Proxy contract that the ballot was created in.
+    ContractAddressChanged {
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        change: ContractAddressChange,
+    },
 }
 
 impl<'a> Notification<'a> {
     pub fn from_voting_state(
-        config: &'a Config,
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
         log: BallotCreatedLog,
         voting_state: VotingState,
     ) -> Self {
         Notification::VotingState {
             config,
+            contract,
             log,
             voting_state,
         }
     }
 
     pub fn from_ballot_info(
-        config: &'a Config,
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
         log: BallotCreatedLog,
         ballot_info: BallotInfo,
     ) -> Self {
         Notification::BallotInfo {
             config,
+            contract,
             log,
             ballot_info,
         }
     }
 
+    pub fn from_finalized_voting_state(
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        log: BallotFinalizedLog,
+        voting_state: VotingState,
+    ) -> Self {
+        Notification::FinalizedVotingState {
+            config,
+            contract,
+            log,
+            voting_state,
+        }
+    }
+
+    pub fn from_finalized_ballot_info(
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        log: BallotFinalizedLog,
+        ballot_info: BallotInfo,
+        quorum_state: QuorumState,
+    ) -> Self {
+        Notification::FinalizedBallotInfo {
+            config,
+            contract,
+            log,
+            ballot_info,
+            quorum_state,
+        }
+    }
+
+    pub fn from_canceled_ballot_info(
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        log: BallotCanceledLog,
+        ballot_info: BallotInfo,
+    ) -> Self {
+        Notification::CanceledBallotInfo {
+            config,
+            contract,
+            log,
+            ballot_info,
+        }
+    }
+
+    pub fn from_reminder(
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        ballot_id: U256,
+        block_number: U256,
+        ballot_state: BallotState,
+        reminder: Reminder,
+        voted: Vec<Address>,
+    ) -> Self {
+        Notification::Reminder {
+            config,
+            contract,
+            ballot_id,
+            block_number,
+            ballot_state,
+            reminder,
+            voted,
+        }
+    }
+
+    pub fn from_vote(
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        log: VoteLog,
+        ballot_state: BallotState,
+        tally: VoteTally,
+    ) -> Self {
+        Notification::Vote {
+            config,
+            contract,
+            log,
+            ballot_state,
+            tally,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `logs` is empty.
+    pub fn from_vote_digest(
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        logs: Vec<VoteLog>,
+        ballot_state: BallotState,
+        tally: VoteTally,
+    ) -> Self {
+        assert!(!logs.is_empty(), "cannot create a vote digest without any votes");
+        Notification::VoteDigest {
+            config,
+            contract,
+            logs,
+            ballot_state,
+            tally,
+        }
+    }
+
+    pub fn from_contract_address_change(
+        config: &'a NetworkConfig,
+        contract: &'a PoaContract,
+        change: ContractAddressChange,
+    ) -> Self {
+        Notification::ContractAddressChanged {
+            config,
+            contract,
+            change,
+        }
+    }
+
     pub fn email_text(&self) -> String {
         format!(
-            "Network: {:?}\n\
+            "Network: {}\n\
              RPC Endpoint: {}\n\
              Block Number: {}\n\
              Contract: {}\n\
              Version: {:?}\n\
              Ballot ID: {}\n\
              {}\n",
-            self.config().network,
+            self.network(),
             self.config().endpoint,
-            self.log().block_number,
+            self.block_number(),
             self.contract_name(),
-            self.config().version,
-            self.log().ballot_id,
+            self.contract().version,
+            self.ballot_id(),
             self.email_body(),
         )
     }
 
-    fn config(&self) -> &Config {
+    /// The configuration of the network that the notification's event occurred on.
+    pub fn config(&self) -> &NetworkConfig {
         match self {
             Notification::VotingState { config, .. } => config,
             Notification::BallotInfo { config, .. } => config,
+            Notification::FinalizedVotingState { config, .. } => config,
+            Notification::FinalizedBallotInfo { config, .. } => config,
+            Notification::CanceledBallotInfo { config, .. } => config,
+            Notification::Reminder { config, .. } => config,
+            Notification::Vote { config, .. } => config,
+            Notification::VoteDigest { config, .. } => config,
+            Notification::ContractAddressChanged { config, .. } => config,
+        }
+    }
+
+    pub fn contract(&self) -> &PoaContract {
+        match self {
+            Notification::VotingState { contract, .. } => contract,
+            Notification::BallotInfo { contract, .. } => contract,
+            Notification::FinalizedVotingState { contract, .. } => contract,
+            Notification::FinalizedBallotInfo { contract, .. } => contract,
+            Notification::CanceledBallotInfo { contract, .. } => contract,
+            Notification::Reminder { contract, .. } => contract,
+            Notification::Vote { contract, .. } => contract,
+            Notification::VoteDigest { contract, .. } => contract,
+            Notification::ContractAddressChanged { contract, .. } => contract,
+        }
+    }
+
+    /// The network that the notification's event occurred on.
+    pub fn network(&self) -> &Network {
+        &self.config().network
+    }
+
+    /// The name of the contract event that generated this notification.
+    pub fn event(&self) -> &'static str {
+        match self {
+            Notification::VotingState { .. } | Notification::BallotInfo { .. } => "BallotCreated",
+            Notification::FinalizedVotingState { .. }
+            | Notification::FinalizedBallotInfo { .. } => "BallotFinalized",
+            Notification::CanceledBallotInfo { .. } => "BallotCanceled",
+            Notification::Reminder { reminder, .. } => match reminder {
+                Reminder::Deadline(_) => "Reminder",
+                Reminder::CanBeFinalized => "CanBeFinalized",
+            },
+            Notification::Vote { .. } | Notification::VoteDigest { .. } => "Vote",
+            Notification::ContractAddressChanged { .. } => "ContractAddressChanged",
+        }
+    }
+
+    pub fn block_number(&self) -> U256 {
+        match self {
+            Notification::VotingState { log, .. } => log.block_number,
+            Notification::BallotInfo { log, .. } => log.block_number,
+            Notification::FinalizedVotingState { log, .. } => log.block_number,
+            Notification::FinalizedBallotInfo { log, .. } => log.block_number,
+            Notification::CanceledBallotInfo { log, .. } => log.block_number,
+            Notification::Reminder { block_number, .. } => *block_number,
+            Notification::Vote { log, .. } => log.block_number,
+            Notification::VoteDigest { logs, .. } => logs.last().unwrap().block_number,
+            Notification::ContractAddressChanged { change, .. } => change.upgrade.block_number,
+        }
+    }
+
+    pub fn ballot_id(&self) -> U256 {
+        match self {
+            Notification::VotingState { log, .. } => log.ballot_id,
+            Notification::BallotInfo { log, .. } => log.ballot_id,
+            Notification::FinalizedVotingState { log, .. } => log.ballot_id,
+            Notification::FinalizedBallotInfo { log, .. } => log.ballot_id,
+            Notification::CanceledBallotInfo { log, .. } => log.ballot_id,
+            Notification::Reminder { ballot_id, .. } => *ballot_id,
+            Notification::Vote { log, .. } => log.ballot_id,
+            Notification::VoteDigest { logs, .. } => logs[0].ballot_id,
+            Notification::ContractAddressChanged { change, .. } => change.upgrade.ballot_id,
+        }
+    }
+
+    pub fn ballot_type(&self) -> BallotType {
+        match self {
+            Notification::VotingState { log, .. } => log.ballot_type,
+            Notification::BallotInfo { log, .. } => log.ballot_type,
+            Notification::FinalizedVotingState { voting_state, .. } => voting_state.ballot_type(),
+            Notification::FinalizedBallotInfo { ballot_info, .. } => ballot_info.ballot_type(),
+            Notification::CanceledBallotInfo { ballot_info, .. } => ballot_info.ballot_type(),
+            Notification::Reminder { ballot_state, .. } => ballot_state.ballot_type(),
+            Notification::Vote { ballot_state, .. } => ballot_state.ballot_type(),
+            Notification::VoteDigest { ballot_state, .. } => ballot_state.ballot_type(),
+            Notification::ContractAddressChanged { change, .. } => {
+                change.upgrade.ballot_state.ballot_type()
+            }
         }
     }
 
-    pub fn log(&self) -> &BallotCreatedLog {
+    /// The state of the notification's ballot at the time the notification was generated.
+    pub fn ballot_state(&self) -> BallotState {
         match self {
-            Notification::VotingState { log, .. } => log,
-            Notification::BallotInfo { log, .. } => log,
+            Notification::VotingState { voting_state, .. } => voting_state.clone().into(),
+            Notification::BallotInfo { ballot_info, .. } => ballot_info.clone().into(),
+            Notification::FinalizedVotingState { voting_state, .. } => voting_state.clone().into(),
+            Notification::FinalizedBallotInfo { ballot_info, .. } => ballot_info.clone().into(),
+            Notification::CanceledBallotInfo { ballot_info, .. } => ballot_info.clone().into(),
+            Notification::Reminder { ballot_state, .. } => ballot_state.clone(),
+            Notification::Vote { ballot_state, .. } => ballot_state.clone(),
+            Notification::VoteDigest { ballot_state, .. } => ballot_state.clone(),
+            Notification::ContractAddressChanged { change, .. } => {
+                change.upgrade.ballot_state.clone()
+            }
+        }
+    }
+
+    /// Returns `false` if `recipient` should not be sent this notification. Deadline reminders are
+    /// not sent to recipients whose voting key has already voted on the ballot.
+    pub fn should_notify(&self, recipient: &Recipient) -> bool {
+        match (self, recipient.voting_key) {
+            (
+                Notification::Reminder { reminder: Reminder::Deadline(_), voted, .. },
+                Some(voting_key),
+            ) => !voted.contains(&voting_key),
+            _ => true,
         }
     }
 
-    fn contract_name(&self) -> String {
+    /// Returns `false` if `recipient`'s subscription does not include the notification's network,
+    /// contract type, or ballot type.
+    pub fn matches_subscription(&self, recipient: &Recipient) -> bool {
+        let subscription = &recipient.subscription;
+        subscription.matches(self.network(), self.contract().kind, self.ballot_type())
+    }
+
+    /// Identifies the ballot and event that this notification was generated for within the
+    /// notification ledger. Returns `None` for vote digests, which are never deduplicated.
+    pub fn ledger_key(&self) -> Option<LedgerKey> {
+        let event = match self {
+            // Each voter can only vote once per ballot, so we use the voter's address to tell
+            // apart the votes cast on the same ballot.
+            Notification::Vote { log, .. } => format!("Vote:0x{}", hex::encode(log.voter.0)),
+            Notification::VoteDigest { .. } => return None,
+            // Each deadline reminder is sent once per offset.
+            Notification::Reminder { reminder: Reminder::Deadline(offset), .. } => {
+                format!("Reminder:{}", format_offset(*offset))
+            }
+            _ => self.event().to_string(),
+        };
+        let ledger_key = LedgerKey::new(
+            self.network(),
+            self.contract().addr,
+            self.ballot_id(),
+            &event,
+        );
+        Some(ledger_key)
+    }
+
+    pub fn contract_name(&self) -> String {
         match self {
             Notification::VotingState { voting_state, .. } => voting_state.contract_name(),
             Notification::BallotInfo { ballot_info, .. } => ballot_info.contract_name(),
+            Notification::FinalizedVotingState { voting_state, .. } => voting_state.contract_name(),
+            Notification::FinalizedBallotInfo { ballot_info, .. } => ballot_info.contract_name(),
+            Notification::CanceledBallotInfo { ballot_info, .. } => ballot_info.contract_name(),
+            Notification::Reminder { ballot_state, .. } => ballot_state.contract_name(),
+            Notification::Vote { ballot_state, .. } => ballot_state.contract_name(),
+            Notification::VoteDigest { ballot_state, .. } => ballot_state.contract_name(),
+            Notification::ContractAddressChanged { change, .. } => {
+                change.upgrade.ballot_state.contract_name()
+            }
+        }
+    }
+
+    /// Converts the notification into a JSON document, the document is printed by `--output json`
+    /// and posted to webhooks.
+    ///
+    /// The document contains the notification's event, network, contract, and ballot id, the
+    /// ballot's state as returned by the contract (every decoded field of the `VotingState` or
+    /// `BallotInfo`), the event-specific fields in `details`, and each address in `watch_list`
+    /// that was found in the ballot in `watch_list_matches`. Addresses and uint256 values are
+    /// formatted as "0x"-prefixed hex strings, and times are formatted as RFC 3339 strings.
+    pub fn to_json(&self, watch_list: &[WatchedAddress]) -> json::Value {
+        let contract = self.contract();
+        let watch_list_matches: Vec<json::Value> = find_matches(watch_list, &self.ballot_state())
+            .iter()
+            .map(|watch_list_match| watch_list_match.to_json())
+            .collect();
+        json::json!({
+            "schema_version": JSON_SCHEMA_VERSION,
+            "event": self.event(),
+            "network": self.network().name(),
+            "chain_id": self.config().chain_id,
+            "contract": {
+                "name": self.contract_name(),
+                "kind": contract.kind,
+                "version": contract.version,
+                "address": contract.addr,
+            },
+            "block_number": self.block_number(),
+            "ballot_id": self.ballot_id(),
+            "ballot_type": self.ballot_type(),
+            "ballot": self.ballot_state(),
+            "details": self.json_details(),
+            "watch_list_matches": watch_list_matches,
+        })
+    }
+
+    fn json_details(&self) -> json::Value {
+        match self {
+            Notification::VotingState { log, .. } | Notification::BallotInfo { log, .. } => {
+                json::json!({ "creator": log.creator, "log": log })
+            }
+            Notification::FinalizedVotingState { log, voting_state, .. } => json::json!({
+                "finalized_by": log.voter,
+                "quorum_state": voting_state.quorum_state(),
+                "log": log,
+            }),
+            Notification::FinalizedBallotInfo { log, quorum_state, .. } => json::json!({
+                "finalized_by": log.voter,
+                "quorum_state": quorum_state,
+                "log": log,
+            }),
+            Notification::CanceledBallotInfo { log, .. } => {
+                json::json!({ "canceled_by": log.voting_key, "log": log })
+            }
+            Notification::Reminder { reminder, voted, .. } => match reminder {
+                Reminder::Deadline(offset) => json::json!({
+                    "reminder": "Deadline",
+                    "offset": format_offset(*offset),
+                    "voted": voted,
+                }),
+                Reminder::CanBeFinalized => json::json!({
+                    "reminder": "CanBeFinalized",
+                    "voted": voted,
+                }),
+            },
+            Notification::Vote { log, tally, .. } => json::json!({
+                "votes": [log],
+                "tally": tally,
+            }),
+            Notification::VoteDigest { logs, tally, .. } => json::json!({
+                "votes": logs,
+                "tally": tally,
+            }),
+            Notification::ContractAddressChanged { change, .. } => json::json!({
+                "target": change.upgrade.target,
+                "target_version": change.upgrade.version,
+                "old_address": change.old_addr,
+                "new_address": change.new_addr,
+            }),
         }
     }
 
@@ -97,108 +502,379 @@ impl<'a> Notification<'a> {
         match self {
             Notification::VotingState { voting_state, .. } => voting_state.email_text(),
             Notification::BallotInfo { ballot_info, .. } => ballot_info.email_text(),
+            Notification::FinalizedVotingState { log, voting_state, .. } => format!(
+                "Ballot Finalized: {:?}\n\
+                 Finalized By: {:?}\n\
+                 {}",
+                voting_state.quorum_state(),
+                log.voter,
+                voting_state.email_text(),
+            ),
+            Notification::FinalizedBallotInfo { log, ballot_info, quorum_state, .. } => format!(
+                "Ballot Finalized: {:?}\n\
+                 Finalized By: {:?}\n\
+                 {}",
+                quorum_state,
+                log.voter,
+                ballot_info.email_text(),
+            ),
+            Notification::CanceledBallotInfo { log, ballot_info, .. } => format!(
+                "Ballot Canceled By: {:?}\n\
+                 {}",
+                log.voting_key,
+                ballot_info.email_text(),
+            ),
+            Notification::Reminder { ballot_state, reminder, .. } => {
+                let reminder_text = match reminder {
+                    Reminder::Deadline(offset) => format!(
+                        "Reminder: Voting Ends In Less Than {}\n",
+                        format_offset(*offset),
+                    ),
+                    Reminder::CanBeFinalized => "Reminder: Ballot Can Be Finalized Now\n".into(),
+                };
+                format!("{}{}", reminder_text, ballot_state.email_text())
+            }
+            Notification::Vote { contract, log, ballot_state, tally, .. } => format!(
+                "{}{}{}",
+                log.email_text(),
+                tally.email_text(contract.kind),
+                ballot_state.email_text(),
+            ),
+            Notification::VoteDigest { contract, logs, ballot_state, tally, .. } => {
+                let votes: Vec<String> = logs.iter().map(VoteLog::email_text).collect();
+                format!(
+                    "Votes Cast: {}\n\
+                     {}{}{}",
+                    logs.len(),
+                    votes.join("\n"),
+                    tally.email_text(contract.kind),
+                    ballot_state.email_text(),
+                )
+            }
+            Notification::ContractAddressChanged { change, .. } => format!(
+                "Contract Address Changed: {:?} ({:?})\n\
+                 Old Address: {:?}\n\
+                 New Address: {:?}\n\
+                 {}",
+                change.upgrade.target,
+                change.upgrade.version,
+                change.old_addr,
+                change.new_addr,
+                change.upgrade.ballot_state.email_text(),
+            ),
         }
     }
 }
 
+/// Sends each notification through the sinks assembled by `Config::sinks`, skipping the
+/// notifications that have already been sent (according to the notification ledger).
 pub struct Notifier<'a> {
-    config: &'a Config,
-    emailer: Option<SmtpTransport>,
+    sinks: Vec<Box<dyn Sink + 'a>>,
     logger: Arc<Mutex<Logger>>,
+    ledger: NotificationLedger,
+    resend: bool,
+    notification_limit: Option<usize>,
     notification_count: usize,
 }
 
 impl<'a> Notifier<'a> {
     pub fn new(config: &'a Config, logger: Arc<Mutex<Logger>>) -> Result<Self> {
-        let emailer = if config.email_notifications {
-            let domain = config.smtp_host_domain.clone().unwrap();
-            let port = config.smtp_port.unwrap();
-            let addr = (domain.as_str(), port);
-            let security = {
-                let tls = TlsConnector::new().map_err(|e| Error::FailedToBuildTls(e))?;
-                let smtp_security_setup = ClientTlsParameters::new(domain.clone(), tls);
-                ClientSecurity::Required(smtp_security_setup)
-            };
-            let creds = Credentials::new(
-                config.smtp_username.clone().unwrap(),
-                config.smtp_password.clone().unwrap(),
-            );
-            let smtp = SmtpClient::new(addr, security)
-                .map_err(|e| Error::FailedToResolveSmtpHostDomain(e))?
-                .connection_reuse(ConnectionReuseParameters::ReuseUnlimited)
-                .authentication_mechanism(Mechanism::Plain)
-                .credentials(creds)
-                .transport();
-            Some(smtp)
-        } else {
-            None
-        };
+        let sinks = config.sinks(&logger)?;
+        let ledger = NotificationLedger::open(&config.state_dir)?;
         Ok(Notifier {
-            config,
-            emailer,
+            sinks,
             logger,
+            ledger,
+            resend: config.resend,
+            notification_limit: config.notification_limit,
             notification_count: 0,
         })
     }
 
-    pub fn notify(&mut self, notif: &Notification) {
-        if self.config.log_emails {
-            self.logger
-                .lock()
-                .unwrap()
-                .log_notification_email_body(notif);
-        } else {
-            self.logger.lock().unwrap().log_notification(notif);
-        }
-        if self.config.email_notifications {
-            for recipient in self.config.email_recipients.iter() {
-                let email: SendableEmail = match self.build_email(notif, recipient) {
-                    Ok(email) => email.into(),
-                    Err(e) => {
-                        self.logger.lock().unwrap().log_failed_to_build_email(e);
-                        continue;
-                    }
-                };
-                if let Err(e) = self.send_email(email) {
-                    self.logger
-                        .lock()
-                        .unwrap()
-                        .log_failed_to_send_email(recipient, e);
-                } else {
-                    self.logger.lock().unwrap().log_email_sent(recipient);
+    /// Sends the notification through each sink. Returns `false` if the notification failed to
+    /// send, i.e. no sink delivered it and at least one sink failed to deliver it.
+    pub fn notify(&mut self, notif: &Notification) -> bool {
+        // Don't notify the recipients about a ballot that they have already been notified about,
+        // unless the user has explicitly asked us to via the `--resend` CLI flag.
+        let sending = self.sinks.iter().any(|sink| sink.delivers());
+        let ledger_key = notif.ledger_key();
+        let already_sent = match ledger_key {
+            Some(ref key) => sending && self.ledger.contains(key),
+            None => false,
+        };
+        if already_sent && !self.resend {
+            self.logger.lock().unwrap().log_notification_already_sent(notif);
+            return true;
+        }
+        // The notification is only recorded in the ledger if at least one sink delivered it; a
+        // notification that every sink skipped or failed to send is not recorded.
+        let (mut delivered, mut failed) = (false, false);
+        for sink in self.sinks.iter_mut() {
+            match sink.send(notif) {
+                Ok(Sent::Delivered) if sink.delivers() => delivered = true,
+                Err(_) if sink.delivers() => failed = true,
+                _ => {}
+            }
+        }
+        if delivered {
+            if let Some(ledger_key) = ledger_key {
+                if let Err(e) = self.ledger.insert(ledger_key) {
+                    self.logger.lock().unwrap().log_failed_to_update_ledger(e);
                 }
             }
         }
         self.notification_count += 1;
+        delivered || !failed
+    }
+
+    /// Called after each block-window has been processed (e.g. sends the email digest if it is
+    /// due).
+    pub fn end_block_window(&mut self) {
+        for sink in self.sinks.iter_mut() {
+            sink.end_block_window();
+        }
+    }
+
+    /// Called when `poagov` shuts down (e.g. sends any notifications that are still waiting for
+    /// the next email digest).
+    pub fn flush(&mut self) {
+        for sink in self.sinks.iter_mut() {
+            sink.flush();
+        }
     }
 
     pub fn reached_limit(&self) -> bool {
-        if let Some(limit) = self.config.notification_limit {
+        if let Some(limit) = self.notification_limit {
             self.notification_count >= limit
         } else {
             false
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{TimeZone, Utc};
+    use web3::types::{Address, U256};
+
+    use super::{Notification, Notifier};
+    use crate::cli::parse_cli_from;
+    use crate::config::{Config, ContractType, PoaContract};
+    use crate::discovery::{ContractAddressChange, ProxyUpgrade};
+    use crate::error::Result;
+    use crate::logger::Logger;
+    use crate::response::common::{BallotCanceledLog, BallotCreatedLog, BallotType};
+    use crate::response::v1::{self, ThresholdVotingState};
+    use crate::response::v2::{BallotInfo, EmissionBallotInfo, ThresholdBallotInfo};
+    use crate::sink::{delivery_result, Sent, Sink};
+    use crate::state::ledger::NotificationLedger;
+    use crate::tests::{
+        setup, sokol_network_config, threshold_ballot_created, v1_keys_proxy_voting_state,
+        SOKOL_NETWORK, V1_VERSION,
+    };
 
-    fn build_email(&self, notif: &Notification, recipient: &str) -> Result<Email> {
-        let outgoing_email = self.config.outgoing_email_addr.clone().unwrap();
-        EmailBuilder::new()
-            .to(recipient)
-            .from(outgoing_email.as_str())
-            .subject("POA Network Governance Notification")
-            .text(notif.email_text())
-            .build()
-            .map_err(|e| Error::FailedToBuildEmail(e))
+    /// Records the event of each notification that it is sent, then reports that the
+    /// notification was sent to `n_sent` destinations and failed to send to `n_failed`.
+    struct MockSink {
+        sent: Arc<Mutex<Vec<String>>>,
+        n_sent: usize,
+        n_failed: usize,
     }
 
-    fn send_email(&mut self, email: SendableEmail) -> Result<()> {
-        if let Some(ref mut emailer) = self.emailer {
-            match emailer.send(email) {
-                Ok(_response) => Ok(()),
-                Err(e) => Err(Error::FailedToSendEmail(e)),
-            }
-        } else {
-            unreachable!("Attempted to send email without SMTP client setup");
+    impl Sink for MockSink {
+        fn name(&self) -> &'static str {
+            "mock"
         }
+
+        fn send(&mut self, notif: &Notification) -> Result<Sent> {
+            self.sent.lock().unwrap().push(notif.event().to_string());
+            delivery_result(self.name(), self.n_sent, self.n_failed)
+        }
+    }
+
+    /// A `Notifier` that sends through `sinks`, using a new ledger in the temp directory.
+    fn notifier<'a>(
+        sinks: Vec<MockSink>,
+        dir_name: &str,
+        notification_limit: usize,
+    ) -> Notifier<'a> {
+        let state_dir = env::temp_dir().join(dir_name);
+        let _ = fs::remove_dir_all(&state_dir);
+        Notifier {
+            sinks: sinks.into_iter().map(|sink| Box::new(sink) as Box<dyn Sink + 'a>).collect(),
+            logger: Arc::new(Mutex::new(Logger::stderr())),
+            ledger: NotificationLedger::open(state_dir.to_str().unwrap()).unwrap(),
+            resend: false,
+            notification_limit: Some(notification_limit),
+            notification_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_notifier_sends_to_each_sink() {
+        let config = sokol_network_config();
+        let notif = threshold_ballot_created(&config);
+        let failing = Arc::new(Mutex::new(vec![]));
+        let working = Arc::new(Mutex::new(vec![]));
+        let failing_sink = MockSink { sent: failing.clone(), n_sent: 0, n_failed: 1 };
+        let mut notifier = notifier(vec![failing_sink], "poagov-test-notifier", 3);
+
+        // A notification that no sink delivered is not recorded in the ledger, so it is sent
+        // again.
+        assert!(!notifier.notify(&notif));
+        assert!(!notifier.notify(&notif));
+        assert_eq!(failing.lock().unwrap().len(), 2);
+        assert!(!notifier.reached_limit());
+
+        notifier.sinks.push(Box::new(MockSink { sent: working.clone(), n_sent: 1, n_failed: 0 }));
+        assert!(notifier.notify(&notif));
+        assert!(notifier.notify(&notif));
+        assert_eq!(failing.lock().unwrap().len(), 3);
+        assert_eq!(*working.lock().unwrap(), vec!["BallotCreated".to_string()]);
+        assert!(notifier.reached_limit());
+    }
+
+    #[test]
+    fn test_skipped_notifications_are_not_recorded() {
+        let config = sokol_network_config();
+        let notif = threshold_ballot_created(&config);
+        let skipping = Arc::new(Mutex::new(vec![]));
+        let skipping_sink = MockSink { sent: skipping.clone(), n_sent: 0, n_failed: 0 };
+        let mut notifier = notifier(vec![skipping_sink], "poagov-test-notifier-skipped", 3);
+
+        // A notification that no destination was sent did not fail, but it is not recorded in
+        // the ledger either.
+        assert!(notifier.notify(&notif));
+        assert!(notifier.notify(&notif));
+        assert_eq!(skipping.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_contract_address_changed_text() {
+        setup();
+        let cli = parse_cli_from(vec!["poagov", "--sokol", "-k", "--latest"]);
+        let config = Config::new(&cli).unwrap();
+        let proxy_contract =
+            PoaContract::read(ContractType::Proxy, &SOKOL_NETWORK, V1_VERSION).unwrap();
+        let (old_addr, new_addr) = (Address::from(1), Address::from(2));
+        let voting_state = v1_keys_proxy_voting_state(v1::QuorumState::Accepted, new_addr);
+        let upgrade =
+            ProxyUpgrade::from_voting_state(&voting_state, U256::from(4), U256::from(100)).unwrap();
+        let change = ContractAddressChange { upgrade, old_addr, new_addr };
+        let notif = Notification::from_contract_address_change(
+            &config.networks[0],
+            &proxy_contract,
+            change,
+        );
+
+        assert_eq!(notif.event(), "ContractAddressChanged");
+        assert_eq!(notif.ballot_id(), U256::from(4));
+        assert_eq!(notif.block_number(), U256::from(100));
+        let text = notif.email_text();
+        assert!(text.contains("Ballot ID: 4\n"));
+        assert!(text.contains("Contract Address Changed: Keys (V1)\n"));
+        assert!(text.contains(&format!("Old Address: {:?}\n", old_addr)));
+        assert!(text.contains(&format!("New Address: {:?}\n", new_addr)));
+    }
+
+    #[test]
+    fn test_canceled_ballot_text() {
+        setup();
+        let cli = parse_cli_from(vec!["poagov", "--sokol", "-e", "--latest"]);
+        let config = Config::new(&cli).unwrap();
+        let emission_contract = &config.networks[0].contracts[0];
+        let log = BallotCanceledLog {
+            block_number: U256::from(100),
+            ballot_id: U256::from(7),
+            voting_key: Address::from(3),
+        };
+        let ballot_info = BallotInfo::Emission(EmissionBallotInfo {
+            creation_time: Utc.timestamp(1_543_424_400, 0),
+            start_time: Utc.timestamp(1_543_424_400, 0),
+            end_time: Utc.timestamp(1_543_597_200, 0),
+            is_canceled: true,
+            is_finalized: false,
+            creator: Address::from(3),
+            memo: "send funds".to_string(),
+            amount: U256::from(100),
+            burn_votes: U256::from(0),
+            freeze_votes: U256::from(0),
+            send_votes: U256::from(0),
+            receiver: Address::from(4),
+        });
+        let notif = Notification::from_canceled_ballot_info(
+            &config.networks[0],
+            emission_contract,
+            log,
+            ballot_info,
+        );
+
+        assert_eq!(notif.event(), "BallotCanceled");
+        let ledger_key = notif.ledger_key().unwrap();
+        assert_eq!(ledger_key.event, "BallotCanceled");
+        assert_eq!(ledger_key.contract, emission_contract.addr);
+        assert_eq!(ledger_key.ballot_id, U256::from(7));
+        let text = notif.email_text();
+        assert!(text.contains(&format!("Ballot Canceled By: {:?}\n", Address::from(3))));
+        assert!(text.contains("Voting was Canceled: true\n"));
+    }
+
+    #[test]
+    fn test_email_text_contains_contract_version() {
+        setup();
+        let cli = parse_cli_from(vec!["poagov", "--sokol", "--v1", "--v2", "-t", "--latest"]);
+        let config = Config::new(&cli).unwrap();
+        let network_config = &config.networks[0];
+        let log = BallotCreatedLog {
+            block_number: U256::from(100),
+            ballot_id: U256::from(5),
+            ballot_type: BallotType::Threshold,
+            creator: Address::zero(),
+        };
+
+        let voting_state = ThresholdVotingState {
+            start_time: Utc.timestamp(1_543_424_400, 0),
+            end_time: Utc.timestamp(1_543_597_200, 0),
+            total_voters: U256::from(0),
+            progress: U256::from(0),
+            is_finalized: false,
+            quorum_state: v1::QuorumState::InProgress,
+            index: U256::from(0),
+            min_threshold_of_voters: U256::from(2),
+            proposed_value: U256::from(3),
+            creator: Address::zero(),
+            memo: "lower the threshold".to_string(),
+        };
+        let notif = Notification::from_voting_state(
+            network_config,
+            &network_config.contracts[0],
+            log,
+            voting_state.into(),
+        );
+        assert!(notif.email_text().contains("Version: V1\n"));
+
+        let ballot_info = ThresholdBallotInfo {
+            start_time: Utc.timestamp(1_543_424_400, 0),
+            end_time: Utc.timestamp(1_543_597_200, 0),
+            total_voters: U256::from(0),
+            progress: U256::from(0),
+            is_finalized: false,
+            proposed_value: U256::from(3),
+            creator: Address::zero(),
+            memo: "lower the threshold".to_string(),
+            can_be_finalized_now: false,
+            already_voted: false,
+        };
+        let notif = Notification::from_ballot_info(
+            network_config,
+            &network_config.contracts[1],
+            log,
+            ballot_info.into(),
+        );
+        assert!(notif.email_text().contains("Version: V2\n"));
     }
 }
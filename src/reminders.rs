@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use web3::types::{Address, U256};
+
+use crate::config::Config;
+use crate::response::common::BallotState;
+
+/// Formats a reminder offset using the largest unit that evenly divides it, e.g. "24h" is
+/// formatted as "1d".
+pub fn format_offset(offset: Duration) -> String {
+    let n_mins = offset.num_minutes();
+    if n_mins % (24 * 60) == 0 {
+        format!("{}d", n_mins / (24 * 60))
+    } else if n_mins % 60 == 0 {
+        format!("{}h", n_mins / 60)
+    } else {
+        format!("{}m", n_mins)
+    }
+}
+
+/// A reminder that is due to be sent for an open ballot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reminder {
+    /// Voting on the ballot ends within this offset.
+    Deadline(Duration),
+    /// The ballot can be finalized (V2 Keys, Threshold, and Proxy ballots only).
+    CanBeFinalized,
+}
+
+#[derive(Debug)]
+struct OpenBallot {
+    end_time: DateTime<Utc>,
+    // The index (into `ReminderScheduler.offsets`) of the next deadline reminder to send.
+    next_offset: usize,
+    // Only V2 Keys, Threshold, and Proxy ballots report whether they can be finalized.
+    finalize_alert_possible: bool,
+    finalize_alert_sent: bool,
+}
+
+/// Tracks the ballots that are open for voting and determines when reminders should be sent for
+/// them.
+///
+/// Ballots are tracked from the time that `poagov` sees their `BallotCreated` event. The ballots
+/// that are already open when `poagov` starts are read from the contracts and tracked at startup.
+#[derive(Debug)]
+pub struct ReminderScheduler {
+    // Sorted from longest to shortest offset.
+    offsets: Vec<Duration>,
+    finalize_alerts: bool,
+    open_ballots: HashMap<(Address, U256), OpenBallot>,
+}
+
+impl ReminderScheduler {
+    pub fn new(config: &Config) -> Self {
+        ReminderScheduler {
+            offsets: config.reminder_offsets.clone(),
+            finalize_alerts: config.finalize_alerts,
+            open_ballots: HashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.offsets.is_empty() || self.finalize_alerts
+    }
+
+    /// Starts tracking a newly created ballot given its current state.
+    pub fn track(&mut self, contract: Address, ballot_id: U256, ballot_state: &BallotState) {
+        if self.is_enabled() {
+            let ballot = OpenBallot {
+                end_time: ballot_state.end_time(),
+                next_offset: 0,
+                finalize_alert_possible: ballot_state.can_be_finalized_now().is_some(),
+                finalize_alert_sent: false,
+            };
+            self.open_ballots.insert((contract, ballot_id), ballot);
+        }
+    }
+
+    /// Stops tracking a ballot (e.g. once the ballot has been finalized or canceled).
+    pub fn untrack(&mut self, contract: Address, ballot_id: U256) {
+        self.open_ballots.remove(&(contract, ballot_id));
+    }
+
+    /// Returns the ids of the ballots in `contract` that may have a reminder due at time `now`.
+    /// The current state of each returned ballot should be passed into `.check()`.
+    pub fn due_ballots(&self, contract: Address, now: DateTime<Utc>) -> Vec<U256> {
+        let mut ballot_ids: Vec<U256> = self
+            .open_ballots
+            .iter()
+            .filter(|((addr, _), _)| *addr == contract)
+            .filter(|(_, ballot)| {
+                let deadline_due = match self.offsets.get(ballot.next_offset) {
+                    Some(offset) => now >= ballot.end_time - *offset,
+                    None => false,
+                };
+                let finalize_alert_pending = self.finalize_alerts
+                    && ballot.finalize_alert_possible
+                    && !ballot.finalize_alert_sent;
+                deadline_due || finalize_alert_pending || now >= ballot.end_time
+            })
+            .map(|((_, ballot_id), _)| *ballot_id)
+            .collect();
+        ballot_ids.sort_unstable();
+        ballot_ids
+    }
+
+    /// Returns the reminders that are due at time `now` for a tracked ballot given the ballot's
+    /// current state. Each reminder is returned only once. If multiple deadline reminders have
+    /// come due since the ballot was last checked, only the shortest is returned.
+    pub fn check(
+        &mut self,
+        contract: Address,
+        ballot_id: U256,
+        ballot_state: &BallotState,
+        now: DateTime<Utc>,
+    ) -> Vec<Reminder> {
+        let key = (contract, ballot_id);
+        if ballot_state.is_closed() {
+            self.open_ballots.remove(&key);
+            return vec![];
+        }
+        let mut reminders = vec![];
+        let untrack = match self.open_ballots.get_mut(&key) {
+            Some(ballot) => {
+                ballot.end_time = ballot_state.end_time();
+                if now < ballot.end_time {
+                    let mut due_offset = None;
+                    while let Some(offset) = self.offsets.get(ballot.next_offset) {
+                        if now < ballot.end_time - *offset {
+                            break;
+                        }
+                        due_offset = Some(*offset);
+                        ballot.next_offset += 1;
+                    }
+                    if let Some(offset) = due_offset {
+                        reminders.push(Reminder::Deadline(offset));
+                    }
+                } else {
+                    ballot.next_offset = self.offsets.len();
+                }
+                let can_be_finalized_now = ballot_state.can_be_finalized_now();
+                if self.finalize_alerts
+                    && !ballot.finalize_alert_sent
+                    && can_be_finalized_now == Some(true)
+                {
+                    reminders.push(Reminder::CanBeFinalized);
+                    ballot.finalize_alert_sent = true;
+                }
+                // Once voting has ended there are no more deadline reminders to send; we only
+                // continue to track the ballot if we are still waiting to send its finalize alert.
+                let awaiting_finalize_alert = self.finalize_alerts
+                    && !ballot.finalize_alert_sent
+                    && can_be_finalized_now.is_some();
+                now >= ballot.end_time && !awaiting_finalize_alert
+            }
+            None => false,
+        };
+        if untrack {
+            self.open_ballots.remove(&key);
+        }
+        reminders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, Utc};
+    use web3::types::{Address, U256};
+
+    use super::{format_offset, OpenBallot, Reminder, ReminderScheduler};
+    use crate::response::common::BallotState;
+    use crate::response::v2::{BallotInfo, EmissionBallotInfo, ThresholdBallotInfo};
+
+    fn threshold_ballot(end_time: DateTime<Utc>, can_be_finalized_now: bool) -> BallotState {
+        let info = ThresholdBallotInfo {
+            start_time: end_time - Duration::days(2),
+            end_time,
+            total_voters: U256::from(0),
+            progress: U256::from(0),
+            is_finalized: false,
+            proposed_value: U256::from(3),
+            creator: Address::zero(),
+            memo: String::new(),
+            can_be_finalized_now,
+            already_voted: false,
+        };
+        BallotState::from(BallotInfo::from(info))
+    }
+
+    #[test]
+    fn test_reminders_are_sent_once_per_offset() {
+        let contract = Address::from(1);
+        let ballot_id = U256::from(7);
+        let end_time = Utc::now() + Duration::days(2);
+        let mut scheduler = ReminderScheduler {
+            offsets: vec![Duration::hours(24), Duration::hours(1)],
+            finalize_alerts: true,
+            open_ballots: Default::default(),
+        };
+        let open = threshold_ballot(end_time, false);
+        scheduler.track(contract, ballot_id, &open);
+
+        // Neither offset is due yet.
+        let now = end_time - Duration::hours(30);
+        assert_eq!(scheduler.check(contract, ballot_id, &open, now), vec![]);
+
+        // The 24h reminder is due, it is only returned once.
+        let now = end_time - Duration::hours(23);
+        assert_eq!(
+            scheduler.check(contract, ballot_id, &open, now),
+            vec![Reminder::Deadline(Duration::hours(24))],
+        );
+        assert_eq!(scheduler.check(contract, ballot_id, &open, now), vec![]);
+
+        // The ballot can be finalized before the 1h reminder comes due.
+        let can_be_finalized = threshold_ballot(end_time, true);
+        assert_eq!(
+            scheduler.check(contract, ballot_id, &can_be_finalized, now),
+            vec![Reminder::CanBeFinalized],
+        );
+
+        // Once voting has ended, the ballot is no longer tracked.
+        let now = end_time + Duration::minutes(1);
+        assert_eq!(scheduler.check(contract, ballot_id, &can_be_finalized, now), vec![]);
+        assert!(scheduler.due_ballots(contract, now).is_empty());
+    }
+
+    #[test]
+    fn test_only_the_shortest_due_offset_is_sent() {
+        let contract = Address::from(1);
+        let ballot_id = U256::from(0);
+        let end_time = Utc::now() + Duration::hours(2);
+        let mut scheduler = ReminderScheduler {
+            offsets: vec![Duration::hours(24), Duration::hours(12), Duration::hours(1)],
+            finalize_alerts: false,
+            open_ballots: Default::default(),
+        };
+        scheduler.open_ballots.insert(
+            (contract, ballot_id),
+            OpenBallot {
+                end_time,
+                next_offset: 0,
+                finalize_alert_possible: true,
+                finalize_alert_sent: false,
+            },
+        );
+        let now = end_time - Duration::minutes(90);
+        assert_eq!(scheduler.due_ballots(contract, now), vec![ballot_id]);
+        let open = threshold_ballot(end_time, false);
+        assert_eq!(
+            scheduler.check(contract, ballot_id, &open, now),
+            vec![Reminder::Deadline(Duration::hours(12))],
+        );
+        assert_eq!(format_offset(Duration::hours(24)), "1d");
+        assert_eq!(format_offset(Duration::minutes(90)), "90m");
+    }
+
+    #[test]
+    fn test_ballots_without_finalize_alerts_are_polled_for_deadlines_only() {
+        let contract = Address::from(1);
+        let ballot_id = U256::from(3);
+        let end_time = Utc::now() + Duration::days(2);
+        let mut scheduler = ReminderScheduler {
+            offsets: vec![Duration::hours(24)],
+            finalize_alerts: true,
+            open_ballots: Default::default(),
+        };
+        // Emission Funds ballots do not report whether they can be finalized.
+        let info = EmissionBallotInfo {
+            creation_time: end_time - Duration::days(2),
+            start_time: end_time - Duration::days(2),
+            end_time,
+            is_canceled: false,
+            is_finalized: false,
+            creator: Address::zero(),
+            memo: String::new(),
+            amount: U256::from(100),
+            burn_votes: U256::from(0),
+            freeze_votes: U256::from(0),
+            send_votes: U256::from(0),
+            receiver: Address::zero(),
+        };
+        scheduler.track(contract, ballot_id, &BallotInfo::from(info).into());
+        let threshold_ballot_id = U256::from(4);
+        scheduler.track(contract, threshold_ballot_id, &threshold_ballot(end_time, false));
+
+        let now = end_time - Duration::hours(30);
+        assert_eq!(scheduler.due_ballots(contract, now), vec![threshold_ballot_id]);
+        let now = end_time - Duration::hours(23);
+        assert_eq!(scheduler.due_ballots(contract, now), vec![ballot_id, threshold_ballot_id]);
+    }
+}
@@ -2,9 +2,13 @@
 #![allow(deprecated)]
 
 use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
 use web3::types::{Address, H256, U256};
 
+use crate::config::ContractType;
 use crate::error::{Error, Result};
+use crate::response::v1::VotingState;
+use crate::response::v2::BallotInfo;
 
 /// Converts a `U256` timestamp to a UTC `DateTime`.
 pub fn u256_to_datetime(uint: U256) -> DateTime<Utc> {
@@ -21,7 +25,7 @@ pub fn u256_to_datetime(uint: U256) -> DateTime<Utc> {
 ///
 /// V2 `KeyTypes` enum (used by the V2 Keys Contract's `ballotInfo`):
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/abstracts/EnumKeyTypes.sol#L5
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum KeyType {
     InvalidKey,
     MiningKey,
@@ -41,6 +45,19 @@ impl From<U256> for KeyType {
     }
 }
 
+/// Converts the `contractType` of a proxy ballot (a value of `ProxyStorage`'s `ContractTypes` enum,
+/// which is the same for V1 and V2) to the type of voting contract that the ballot targets.
+/// Returns `None` if the ballot targets a contract other than a voting contract (e.g. the
+/// `KeysManager` or `BallotsStorage` contracts).
+pub fn u256_to_contract_type(contract_type: U256) -> Option<ContractType> {
+    match contract_type.low_u64() {
+        2 => Some(ContractType::Keys),
+        3 => Some(ContractType::Threshold),
+        4 => Some(ContractType::Proxy),
+        _ => None,
+    }
+}
+
 /// V1 Keys Contract (used in `BallotCreated` event and within the `votingState`):
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/aa45e19ca50f7cae308c1281d950245b0c65182a/contracts/VotingToChangeKeys.sol#L10
 ///
@@ -54,7 +71,7 @@ impl From<U256> for KeyType {
 ///
 /// V2 - all contracts use the same enum:
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/abstracts/EnumBallotTypes.sol#L5
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum BallotType {
     InvalidKey,
     AddKey,
@@ -88,6 +105,92 @@ impl From<H256> for BallotType {
     }
 }
 
+/// The current state of a ballot, as returned by a V1 contract's `.votingState()` function or a
+/// V2 contract's `.getBallotInfo()` function.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum BallotState {
+    VotingState(VotingState),
+    BallotInfo(BallotInfo),
+}
+
+impl From<VotingState> for BallotState {
+    fn from(voting_state: VotingState) -> Self {
+        BallotState::VotingState(voting_state)
+    }
+}
+
+impl From<BallotInfo> for BallotState {
+    fn from(ballot_info: BallotInfo) -> Self {
+        BallotState::BallotInfo(ballot_info)
+    }
+}
+
+impl BallotState {
+    pub fn contract_name(&self) -> String {
+        match self {
+            BallotState::VotingState(voting_state) => voting_state.contract_name(),
+            BallotState::BallotInfo(ballot_info) => ballot_info.contract_name(),
+        }
+    }
+
+    pub fn ballot_type(&self) -> BallotType {
+        match self {
+            BallotState::VotingState(voting_state) => voting_state.ballot_type(),
+            BallotState::BallotInfo(ballot_info) => ballot_info.ballot_type(),
+        }
+    }
+
+    pub fn end_time(&self) -> DateTime<Utc> {
+        match self {
+            BallotState::VotingState(voting_state) => voting_state.end_time(),
+            BallotState::BallotInfo(ballot_info) => ballot_info.end_time(),
+        }
+    }
+
+    pub fn memo(&self) -> &str {
+        match self {
+            BallotState::VotingState(voting_state) => voting_state.memo(),
+            BallotState::BallotInfo(ballot_info) => ballot_info.memo(),
+        }
+    }
+
+    /// Returns `true` if the ballot has been finalized or canceled, i.e. the ballot can no longer
+    /// be voted on.
+    pub fn is_closed(&self) -> bool {
+        match self {
+            BallotState::VotingState(voting_state) => voting_state.is_finalized(),
+            BallotState::BallotInfo(ballot_info) => {
+                ballot_info.is_finalized() || ballot_info.is_canceled()
+            }
+        }
+    }
+
+    /// V1 contracts do not return a `canBeFinalizedNow` field, so `None` is returned for V1
+    /// ballots.
+    pub fn can_be_finalized_now(&self) -> Option<bool> {
+        match self {
+            BallotState::VotingState(_) => None,
+            BallotState::BallotInfo(ballot_info) => ballot_info.can_be_finalized_now(),
+        }
+    }
+
+    /// Every address field of the ballot, paired with the field's name.
+    pub fn addresses(&self) -> Vec<(&'static str, Address)> {
+        match self {
+            BallotState::VotingState(voting_state) => voting_state.addresses(),
+            BallotState::BallotInfo(ballot_info) => ballot_info.addresses(),
+        }
+    }
+
+    pub fn email_text(&self) -> String {
+        match self {
+            BallotState::VotingState(voting_state) => voting_state.email_text(),
+            BallotState::BallotInfo(ballot_info) => ballot_info.email_text(),
+        }
+    }
+}
+
 /// A parsed `BallotCreated` event log. All V1 and V2 contracts use the same `BallotCreated` event.
 ///
 /// V1 Keys Contract's `BallotCreated` event:
@@ -101,7 +204,7 @@ impl From<H256> for BallotType {
 ///
 /// V2 - all contracts use the same `BallotCreated` event:
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/abstracts/VotingTo.sol#L30
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize)]
 pub struct BallotCreatedLog {
     pub block_number: U256,
     pub ballot_id: U256,
@@ -150,3 +253,276 @@ impl BallotCreatedLog {
         })
     }
 }
+
+/// A parsed `BallotCanceled` event log. Only the V2 Emission Funds contract emits the
+/// `BallotCanceled` event, a ballot can be canceled by its creator shortly after it was created.
+///
+/// V2 Emission Funds Contract's `BallotCanceled` event:
+/// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/VotingToManageEmissionFunds.sol
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct BallotCanceledLog {
+    pub block_number: U256,
+    pub ballot_id: U256,
+    pub voting_key: Address,
+}
+
+impl BallotCanceledLog {
+    pub fn from_ethabi_log(log: ethabi::Log, block_number: U256) -> Result<Self> {
+        let mut ballot_id: Option<U256> = None;
+        let mut voting_key: Option<Address> = None;
+        for ethabi::LogParam { name, value } in log.params {
+            match name.as_ref() {
+                "id" => ballot_id = value.to_uint(),
+                "votingKey" => voting_key = value.to_address(),
+                name => unreachable!("Found unknown `BallotCanceled` event log field: {}", name),
+            };
+        }
+        let ballot_id = match ballot_id {
+            Some(id) => id,
+            None => return Err(Error::FailedToParseBallotCanceledLog("missing `id`".into())),
+        };
+        let voting_key = match voting_key {
+            Some(voting_key) => voting_key,
+            None => {
+                return Err(Error::FailedToParseBallotCanceledLog(
+                    "missing `votingKey`".to_string(),
+                ))
+            }
+        };
+        Ok(BallotCanceledLog {
+            ballot_id,
+            voting_key,
+            block_number,
+        })
+    }
+}
+
+/// A parsed `BallotFinalized` event log. All V1 and V2 contracts use the same `BallotFinalized`
+/// event.
+///
+/// V1 Keys Contract's `BallotFinalized` event:
+/// https://github.com/poanetwork/poa-network-consensus-contracts/blob/aa45e19ca50f7cae308c1281d950245b0c65182a/contracts/VotingToChangeKeys.sol
+///
+/// V2 - all contracts use the same `BallotFinalized` event:
+/// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/abstracts/VotingTo.sol
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct BallotFinalizedLog {
+    pub block_number: U256,
+    pub ballot_id: U256,
+    pub voter: Address,
+}
+
+impl BallotFinalizedLog {
+    pub fn from_ethabi_log(log: ethabi::Log, block_number: U256) -> Result<Self> {
+        let mut ballot_id: Option<U256> = None;
+        let mut voter: Option<Address> = None;
+        for ethabi::LogParam { name, value } in log.params {
+            match name.as_ref() {
+                "id" => ballot_id = value.to_uint(),
+                "voter" => voter = value.to_address(),
+                name => unreachable!("Found unknown `BallotFinalized` event log field: {}", name),
+            };
+        }
+        let ballot_id = match ballot_id {
+            Some(id) => id,
+            None => return Err(Error::FailedToParseBallotFinalizedLog("missing `id`".into())),
+        };
+        let voter = match voter {
+            Some(voter) => voter,
+            None => {
+                return Err(Error::FailedToParseBallotFinalizedLog(
+                    "missing `voter`".to_string(),
+                ))
+            }
+        };
+        Ok(BallotFinalizedLog {
+            ballot_id,
+            voter,
+            block_number,
+        })
+    }
+}
+
+/// Identifies the choice that a validator made when voting on a ballot.
+///
+/// The V1 and V2 Keys, Threshold, and Proxy contracts use the same `ActionChoice` enum:
+/// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/abstracts/VotingToChange.sol
+///
+/// The V2 Emission contract uses its own `ActionChoice` enum:
+/// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/VotingToManageEmissionFunds.sol
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum VoteChoice {
+    Invalid,
+    Accept,
+    Reject,
+    Send,
+    Burn,
+    Freeze,
+}
+
+impl VoteChoice {
+    pub fn new(uint: U256, contract_type: ContractType) -> Self {
+        match (contract_type, uint.low_u64()) {
+            (_, 0) => VoteChoice::Invalid,
+            (ContractType::Emission, 1) => VoteChoice::Send,
+            (ContractType::Emission, 2) => VoteChoice::Burn,
+            (ContractType::Emission, 3) => VoteChoice::Freeze,
+            (_, 1) => VoteChoice::Accept,
+            (_, 2) => VoteChoice::Reject,
+            _ => unreachable!("unrecognized `ActionChoice`: {}", uint),
+        }
+    }
+}
+
+/// A parsed `Vote` event log.
+///
+/// V1 contracts' `Vote` event does not contain the `voterMiningKey` field:
+/// https://github.com/poanetwork/poa-network-consensus-contracts/blob/aa45e19ca50f7cae308c1281d950245b0c65182a/contracts/VotingToChangeKeys.sol
+///
+/// V2 - all contracts use the same `Vote` event:
+/// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/abstracts/VotingTo.sol
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct VoteLog {
+    pub block_number: U256,
+    pub ballot_id: U256,
+    pub decision: VoteChoice,
+    pub voter: Address,
+    pub time: DateTime<Utc>,
+    pub voter_mining_key: Option<Address>,
+}
+
+impl VoteLog {
+    pub fn from_ethabi_log(
+        log: ethabi::Log,
+        block_number: U256,
+        contract_type: ContractType,
+    ) -> Result<Self> {
+        let mut ballot_id: Option<U256> = None;
+        let mut decision: Option<VoteChoice> = None;
+        let mut voter: Option<Address> = None;
+        let mut time: Option<DateTime<Utc>> = None;
+        let mut voter_mining_key: Option<Address> = None;
+        for ethabi::LogParam { name, value } in log.params {
+            match name.as_ref() {
+                "id" => ballot_id = value.to_uint(),
+                "decision" => {
+                    decision = value.to_uint().map(|uint| VoteChoice::new(uint, contract_type))
+                }
+                "voter" => voter = value.to_address(),
+                "time" => time = value.to_uint().map(u256_to_datetime),
+                "voterMiningKey" => voter_mining_key = value.to_address(),
+                name => unreachable!("Found unknown `Vote` event log field: {}", name),
+            };
+        }
+        let ballot_id = match ballot_id {
+            Some(id) => id,
+            None => return Err(Error::FailedToParseVoteLog("missing `id`".into())),
+        };
+        let decision = match decision {
+            Some(decision) => decision,
+            None => return Err(Error::FailedToParseVoteLog("missing `decision`".into())),
+        };
+        let voter = match voter {
+            Some(voter) => voter,
+            None => return Err(Error::FailedToParseVoteLog("missing `voter`".into())),
+        };
+        let time = match time {
+            Some(time) => time,
+            None => return Err(Error::FailedToParseVoteLog("missing `time`".into())),
+        };
+        Ok(VoteLog {
+            block_number,
+            ballot_id,
+            decision,
+            voter,
+            time,
+            voter_mining_key,
+        })
+    }
+
+    pub fn email_text(&self) -> String {
+        let mut text = format!(
+            "Vote: {:?}\n\
+             Voter: {:?}\n",
+            self.decision,
+            self.voter,
+        );
+        if let Some(voter_mining_key) = self.voter_mining_key {
+            text.push_str(&format!("Voter Mining Key: {:?}\n", voter_mining_key));
+        }
+        text.push_str(&format!("Vote Time: {}\n", self.time));
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use ethabi::{Log, LogParam, Token};
+    use serde_json::{json, to_value};
+    use web3::types::{Address, U256};
+
+    use super::{BallotCanceledLog, BallotCreatedLog, BallotState, BallotType, KeyType};
+    use crate::response::v2::{BallotInfo, KeysBallotInfo};
+
+    #[test]
+    fn test_json_serialization() {
+        let log = BallotCreatedLog {
+            block_number: U256::from(5_000_000),
+            ballot_id: U256::from(12),
+            ballot_type: BallotType::AddKey,
+            creator: Address::from(1),
+        };
+        assert_eq!(
+            to_value(log).unwrap(),
+            json!({
+                "block_number": "0x4c4b40",
+                "ballot_id": "0xc",
+                "ballot_type": "AddKey",
+                "creator": "0x0000000000000000000000000000000000000001",
+            }),
+        );
+
+        let info = KeysBallotInfo {
+            start_time: Utc.timestamp(1_543_424_400, 0),
+            end_time: Utc.timestamp(1_543_597_200, 0),
+            affected_key: Address::from(2),
+            affected_key_type: KeyType::MiningKey,
+            new_voting_key: Address::zero(),
+            new_payout_key: Address::zero(),
+            mining_key: Address::zero(),
+            total_voters: U256::from(3),
+            progress: U256::from(1),
+            is_finalized: false,
+            ballot_type: BallotType::AddKey,
+            creator: Address::from(1),
+            memo: "add a validator".to_string(),
+            can_be_finalized_now: false,
+        };
+        let state = to_value(BallotState::from(BallotInfo::from(info))).unwrap();
+        assert_eq!(state["contract"], "Keys");
+        assert_eq!(state["start_time"], "2018-11-28T17:00:00Z");
+        assert_eq!(state["affected_key_type"], "MiningKey");
+        assert_eq!(state["total_voters"], "0x3");
+        assert_eq!(state["memo"], "add a validator");
+    }
+
+    #[test]
+    fn test_ballot_canceled_log_from_ethabi_log() {
+        let log = Log {
+            params: vec![
+                LogParam { name: "id".to_string(), value: Token::Uint(U256::from(7)) },
+                LogParam { name: "votingKey".to_string(), value: Token::Address(Address::from(3)) },
+            ],
+        };
+        let log = BallotCanceledLog::from_ethabi_log(log, U256::from(5_000_000)).unwrap();
+        assert_eq!(log.block_number, U256::from(5_000_000));
+        assert_eq!(log.ballot_id, U256::from(7));
+        assert_eq!(log.voting_key, Address::from(3));
+
+        let log = Log {
+            params: vec![LogParam { name: "id".to_string(), value: Token::Uint(U256::from(7)) }],
+        };
+        assert!(BallotCanceledLog::from_ethabi_log(log, U256::from(5_000_000)).is_err());
+    }
+}
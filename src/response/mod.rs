@@ -1,3 +1,13 @@
 pub mod common;
 pub mod v1;
 pub mod v2;
+
+/// The version of the JSON schema used to serialize the response types and notifications (see
+/// `Notification::to_json`). Fields may be added without changing the version, the version is
+/// incremented whenever a field is removed, renamed, or changes meaning.
+///
+/// Addresses and uint256 values are serialized as "0x"-prefixed hex strings (as in Ethereum's
+/// JSON-RPC API), times are serialized as RFC 3339 strings, and enums are serialized as their
+/// variant's name. `VotingState` and `BallotInfo` contain a `contract` field naming the contract
+/// type that the ballot was decoded from.
+pub const JSON_SCHEMA_VERSION: u64 = 1;
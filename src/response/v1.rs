@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use ethabi;
+use serde_derive::Serialize;
 use web3::types::{Address, U256};
 
 use crate::response::common::{u256_to_datetime, BallotType, KeyType};
@@ -16,7 +17,7 @@ use crate::response::common::{u256_to_datetime, BallotType, KeyType};
 ///
 /// V1 Proxy Contract:
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/aa45e19ca50f7cae308c1281d950245b0c65182a/contracts/VotingToChangeProxyAddress.sol#L10
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize)]
 pub enum QuorumState {
     Invalid,
     InProgress,
@@ -36,7 +37,8 @@ impl From<U256> for QuorumState {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "contract")]
 pub enum VotingState {
     Keys(KeysVotingState),
     Threshold(ThresholdVotingState),
@@ -70,6 +72,62 @@ impl VotingState {
         }
     }
 
+    pub fn ballot_type(&self) -> BallotType {
+        match self {
+            VotingState::Keys(state) => state.ballot_type,
+            VotingState::Threshold(_) => BallotType::Threshold,
+            VotingState::Proxy(_) => BallotType::Proxy,
+        }
+    }
+
+    pub fn quorum_state(&self) -> QuorumState {
+        match self {
+            VotingState::Keys(state) => state.quorum_state,
+            VotingState::Threshold(state) => state.quorum_state,
+            VotingState::Proxy(state) => state.quorum_state,
+        }
+    }
+
+    pub fn end_time(&self) -> DateTime<Utc> {
+        match self {
+            VotingState::Keys(state) => state.end_time,
+            VotingState::Threshold(state) => state.end_time,
+            VotingState::Proxy(state) => state.end_time,
+        }
+    }
+
+    pub fn memo(&self) -> &str {
+        match self {
+            VotingState::Keys(state) => &state.memo,
+            VotingState::Threshold(state) => &state.memo,
+            VotingState::Proxy(state) => &state.memo,
+        }
+    }
+
+    pub fn is_finalized(&self) -> bool {
+        match self {
+            VotingState::Keys(state) => state.is_finalized,
+            VotingState::Threshold(state) => state.is_finalized,
+            VotingState::Proxy(state) => state.is_finalized,
+        }
+    }
+
+    /// Every address field of the voting state, paired with the field's name.
+    pub fn addresses(&self) -> Vec<(&'static str, Address)> {
+        match self {
+            VotingState::Keys(state) => vec![
+                ("affected_key", state.affected_key),
+                ("mining_key", state.mining_key),
+                ("creator", state.creator),
+            ],
+            VotingState::Threshold(state) => vec![("creator", state.creator)],
+            VotingState::Proxy(state) => vec![
+                ("proposed_value", state.proposed_value),
+                ("creator", state.creator),
+            ],
+        }
+    }
+
     pub fn email_text(&self) -> String {
         match self {
             VotingState::Keys(state) => state.email_text(),
@@ -81,7 +139,7 @@ impl VotingState {
 
 /// V1 Key's Contract:
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/aa45e19ca50f7cae308c1281d950245b0c65182a/contracts/VotingToChangeKeys.sol#L22
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct KeysVotingState {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
@@ -171,7 +229,7 @@ impl KeysVotingState {
 
 /// V1 Threshold Contract:
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/aa45e19ca50f7cae308c1281d950245b0c65182a/contracts/VotingToChangeMinThreshold.sol#L20
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ThresholdVotingState {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
@@ -246,7 +304,7 @@ impl ThresholdVotingState {
 
 /// V1 Proxy Contract:
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/aa45e19ca50f7cae308c1281d950245b0c65182a/contracts/VotingToChangeProxyAddress.sol#L19
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ProxyVotingState {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
@@ -1,7 +1,9 @@
 use chrono::{DateTime, Utc};
 use ethabi;
+use serde_derive::Serialize;
 use web3::types::{Address, U256};
 
+use crate::config::ContractType;
 use crate::response::common::{u256_to_datetime, BallotType, KeyType};
 
 /// Converts the `amount` field found in the `VotingToManageEmissionFunds` contract from Wei to
@@ -20,7 +22,42 @@ fn convert_wei_to_poa(amount_in_wei: U256) -> f64 {
     whole_poa.low_u64() as f64 + fraction_of_a_poa
 }
 
-#[derive(Clone, Debug)]
+/// Describes the current state of a given ballot, returned by each V2 contract's
+/// `.getQuorumState()` function.
+///
+/// The V2 Keys, Threshold, and Proxy contracts use the same `QuorumStates` enum:
+/// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/abstracts/VotingToChange.sol
+///
+/// The V2 Emission contract uses its own `QuorumStates` enum:
+/// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/VotingToManageEmissionFunds.sol
+#[derive(Clone, Copy, Debug, Serialize)]
+pub enum QuorumState {
+    Invalid,
+    InProgress,
+    Accepted,
+    Rejected,
+    Sent,
+    Burnt,
+    Frozen,
+}
+
+impl QuorumState {
+    pub fn new(uint: U256, contract_type: ContractType) -> Self {
+        match (contract_type, uint.low_u64()) {
+            (_, 0) => QuorumState::Invalid,
+            (_, 1) => QuorumState::InProgress,
+            (ContractType::Emission, 2) => QuorumState::Sent,
+            (ContractType::Emission, 3) => QuorumState::Burnt,
+            (ContractType::Emission, 4) => QuorumState::Frozen,
+            (_, 2) => QuorumState::Accepted,
+            (_, 3) => QuorumState::Rejected,
+            _ => unreachable!("unrecognized `QuorumState`: {}", uint),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "contract")]
 pub enum BallotInfo {
     Keys(KeysBallotInfo),
     Threshold(ThresholdBallotInfo),
@@ -62,6 +99,83 @@ impl BallotInfo {
         }
     }
 
+    pub fn ballot_type(&self) -> BallotType {
+        match self {
+            BallotInfo::Keys(info) => info.ballot_type,
+            BallotInfo::Threshold(_) => BallotType::Threshold,
+            BallotInfo::Proxy(_) => BallotType::Proxy,
+            BallotInfo::Emission(_) => BallotType::Emission,
+        }
+    }
+
+    pub fn end_time(&self) -> DateTime<Utc> {
+        match self {
+            BallotInfo::Keys(info) => info.end_time,
+            BallotInfo::Threshold(info) => info.end_time,
+            BallotInfo::Proxy(info) => info.end_time,
+            BallotInfo::Emission(info) => info.end_time,
+        }
+    }
+
+    pub fn memo(&self) -> &str {
+        match self {
+            BallotInfo::Keys(info) => &info.memo,
+            BallotInfo::Threshold(info) => &info.memo,
+            BallotInfo::Proxy(info) => &info.memo,
+            BallotInfo::Emission(info) => &info.memo,
+        }
+    }
+
+    pub fn is_finalized(&self) -> bool {
+        match self {
+            BallotInfo::Keys(info) => info.is_finalized,
+            BallotInfo::Threshold(info) => info.is_finalized,
+            BallotInfo::Proxy(info) => info.is_finalized,
+            BallotInfo::Emission(info) => info.is_finalized,
+        }
+    }
+
+    /// Only Emission Funds ballots can be canceled.
+    pub fn is_canceled(&self) -> bool {
+        match self {
+            BallotInfo::Emission(info) => info.is_canceled,
+            _ => false,
+        }
+    }
+
+    /// The Emission Funds contract's `.getBallotInfo()` does not return a `canBeFinalizedNow`
+    /// field, so `None` is returned for Emission Funds ballots.
+    pub fn can_be_finalized_now(&self) -> Option<bool> {
+        match self {
+            BallotInfo::Keys(info) => Some(info.can_be_finalized_now),
+            BallotInfo::Threshold(info) => Some(info.can_be_finalized_now),
+            BallotInfo::Proxy(info) => Some(info.can_be_finalized_now),
+            BallotInfo::Emission(_) => None,
+        }
+    }
+
+    /// Every address field of the ballot info, paired with the field's name.
+    pub fn addresses(&self) -> Vec<(&'static str, Address)> {
+        match self {
+            BallotInfo::Keys(info) => vec![
+                ("affected_key", info.affected_key),
+                ("new_voting_key", info.new_voting_key),
+                ("new_payout_key", info.new_payout_key),
+                ("mining_key", info.mining_key),
+                ("creator", info.creator),
+            ],
+            BallotInfo::Threshold(info) => vec![("creator", info.creator)],
+            BallotInfo::Proxy(info) => vec![
+                ("proposed_value", info.proposed_value),
+                ("creator", info.creator),
+            ],
+            BallotInfo::Emission(info) => vec![
+                ("receiver", info.receiver),
+                ("creator", info.creator),
+            ],
+        }
+    }
+
     pub fn email_text(&self) -> String {
         match self {
             BallotInfo::Keys(info) => info.email_text(),
@@ -74,7 +188,7 @@ impl BallotInfo {
 
 /// Returned by the V2 Keys contract's `.getBallotInfo()` function:
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/VotingToChangeKeys.sol#L7
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct KeysBallotInfo {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
@@ -166,7 +280,7 @@ impl KeysBallotInfo {
 
 /// Returned by the V2 Threshold Contract's `.getBallotInfo()` function:
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/VotingToChangeMinThreshold.sol#L30
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ThresholdBallotInfo {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
@@ -236,7 +350,7 @@ impl ThresholdBallotInfo {
 
 /// Returned by the V2 Proxy Contract's `.getBallotInfo()` function:
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/VotingToChangeProxyAddress.sol#L30
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ProxyBallotInfo {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
@@ -309,7 +423,7 @@ impl ProxyBallotInfo {
 
 /// Returned by the V2 Emission Contract's `.getBallotInfo()` function:
 /// https://github.com/poanetwork/poa-network-consensus-contracts/blob/ec307069302fdf6647e8b1bdc13093960913b266/contracts/VotingToManageEmissionFunds.sol#L126
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct EmissionBallotInfo {
     pub creation_time: DateTime<Utc>,
     pub start_time: DateTime<Utc>,
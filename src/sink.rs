@@ -0,0 +1,277 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+
+use crate::chat::ChatWebhook;
+use crate::error::{Error, Result};
+use crate::logger::Logger;
+use crate::notify::Notification;
+use crate::telegram::TelegramBot;
+use crate::watch::{find_matches, WatchedAddress};
+use crate::webhook::{JsonWebhook, RetriedPost};
+
+/// A channel that notifications are sent through (e.g. email or Telegram). The `Notifier` sends
+/// each notification to every sink assembled by `Config::sinks`.
+pub trait Sink: Send {
+    /// The sink's name, used in errors.
+    fn name(&self) -> &'static str;
+
+    /// Returns `false` if the sink only reports notifications locally (i.e. to the logs or to
+    /// stdout). Notifications that were only reported locally are not recorded in the
+    /// notification ledger.
+    fn delivers(&self) -> bool {
+        true
+    }
+
+    /// Sends the notification to each of the sink's destinations. The sink logs whether or not
+    /// each destination was reached; an error is returned if every destination failed.
+    fn send(&mut self, notif: &Notification) -> Result<Sent>;
+
+    /// Called after each block-window has been processed.
+    fn end_block_window(&mut self) {}
+
+    /// Called when `poagov` shuts down.
+    fn flush(&mut self) {}
+}
+
+/// The outcome of a sink's `send` that did not fail.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sent {
+    /// The notification was sent to (or queued for) at least one destination.
+    Delivered,
+    /// None of the sink's destinations were sent the notification, e.g. no recipient is
+    /// subscribed to it. Notifications that were skipped are not recorded in the ledger.
+    Skipped,
+}
+
+/// Returns the result of a sink's `send`, an error is only returned if the notification was sent
+/// to no destinations and failed to send to at least one destination.
+pub fn delivery_result(sink: &str, n_sent: usize, n_failed: usize) -> Result<Sent> {
+    if n_sent > 0 {
+        Ok(Sent::Delivered)
+    } else if n_failed > 0 {
+        Err(Error::NotificationNotDelivered {
+            sink: sink.to_string(),
+            n_failed,
+        })
+    } else {
+        Ok(Sent::Skipped)
+    }
+}
+
+/// Logs each notification, or each notification's email body when `--log-emails` is set. A
+/// warning is logged for each watched address found in the notification's ballot.
+pub struct LogSink {
+    log_emails: bool,
+    watch_list: Vec<WatchedAddress>,
+    logger: Arc<Mutex<Logger>>,
+}
+
+impl LogSink {
+    pub fn new(
+        log_emails: bool,
+        watch_list: Vec<WatchedAddress>,
+        logger: Arc<Mutex<Logger>>,
+    ) -> Self {
+        LogSink {
+            log_emails,
+            watch_list,
+            logger,
+        }
+    }
+}
+
+impl Sink for LogSink {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    fn delivers(&self) -> bool {
+        false
+    }
+
+    fn send(&mut self, notif: &Notification) -> Result<Sent> {
+        if self.log_emails {
+            self.logger.lock().unwrap().log_notification_email_body(notif);
+        } else {
+            self.logger.lock().unwrap().log_notification(notif);
+        }
+        for alert in find_matches(&self.watch_list, &notif.ballot_state()) {
+            self.logger.lock().unwrap().log_watch_list_alert(notif, &alert);
+        }
+        Ok(Sent::Delivered)
+    }
+}
+
+/// Prints each notification's JSON document to stdout (`--output json`).
+pub struct JsonOutputSink {
+    watch_list: Vec<WatchedAddress>,
+}
+
+impl JsonOutputSink {
+    pub fn new(watch_list: Vec<WatchedAddress>) -> Self {
+        JsonOutputSink { watch_list }
+    }
+}
+
+impl Sink for JsonOutputSink {
+    fn name(&self) -> &'static str {
+        "json-output"
+    }
+
+    fn delivers(&self) -> bool {
+        false
+    }
+
+    fn send(&mut self, notif: &Notification) -> Result<Sent> {
+        println!("{}", notif.to_json(&self.watch_list));
+        Ok(Sent::Delivered)
+    }
+}
+
+/// Posts each notification to the chat webhooks configured for the notification's network.
+pub struct ChatSink {
+    chat: ChatWebhook,
+    logger: Arc<Mutex<Logger>>,
+}
+
+impl ChatSink {
+    pub fn new(watch_list: Vec<WatchedAddress>, logger: Arc<Mutex<Logger>>) -> Self {
+        ChatSink {
+            chat: ChatWebhook::new(watch_list),
+            logger,
+        }
+    }
+}
+
+impl Sink for ChatSink {
+    fn name(&self) -> &'static str {
+        "chat"
+    }
+
+    fn send(&mut self, notif: &Notification) -> Result<Sent> {
+        let (mut n_sent, mut n_failed) = (0, 0);
+        for url in notif.config().chat_webhooks.iter() {
+            if let Err(e) = self.chat.post(url, notif) {
+                self.logger.lock().unwrap().log_failed_to_post_to_webhook(url, e);
+                n_failed += 1;
+            } else {
+                self.logger.lock().unwrap().log_posted_to_webhook(url);
+                n_sent += 1;
+            }
+        }
+        delivery_result(self.name(), n_sent, n_failed)
+    }
+}
+
+/// Sends each notification to the configured Telegram chats.
+pub struct TelegramSink {
+    bot: TelegramBot,
+    chat_ids: Vec<String>,
+    logger: Arc<Mutex<Logger>>,
+}
+
+impl TelegramSink {
+    pub fn new(bot: TelegramBot, chat_ids: Vec<String>, logger: Arc<Mutex<Logger>>) -> Self {
+        TelegramSink { bot, chat_ids, logger }
+    }
+}
+
+impl Sink for TelegramSink {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    fn send(&mut self, notif: &Notification) -> Result<Sent> {
+        let (mut n_sent, mut n_failed) = (0, 0);
+        for chat_id in self.chat_ids.iter() {
+            if let Err(e) = self.bot.send(chat_id, notif) {
+                self.logger
+                    .lock()
+                    .unwrap()
+                    .log_failed_to_send_telegram_message(chat_id, e);
+                n_failed += 1;
+            } else {
+                self.logger.lock().unwrap().log_telegram_message_sent(chat_id);
+                n_sent += 1;
+            }
+        }
+        delivery_result(self.name(), n_sent, n_failed)
+    }
+}
+
+/// Posts each notification's JSON document to the configured webhooks.
+pub struct WebhookSink {
+    webhook: JsonWebhook,
+    urls: Vec<String>,
+    logger: Arc<Mutex<Logger>>,
+}
+
+impl WebhookSink {
+    pub fn new(webhook: JsonWebhook, urls: Vec<String>, logger: Arc<Mutex<Logger>>) -> Self {
+        WebhookSink { webhook, urls, logger }
+    }
+}
+
+impl WebhookSink {
+    fn log_retried(&self, retried: Vec<RetriedPost>) {
+        let mut logger = self.logger.lock().unwrap();
+        for post in retried {
+            match post.result {
+                Ok(()) => logger.log_posted_to_webhook(&post.url),
+                Err(e) => {
+                    logger.log_failed_to_post_to_webhook(&post.url, e);
+                    match post.next_attempt_at {
+                        Some(next_attempt_at) => {
+                            logger.log_webhook_post_queued_for_retry(&post.url, next_attempt_at)
+                        }
+                        None => logger.log_gave_up_on_webhook_post(&post.url),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Sink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    /// A request that failed is queued to be retried after the block-window, queued requests
+    /// count as delivered.
+    fn send(&mut self, notif: &Notification) -> Result<Sent> {
+        let (mut n_sent, mut n_failed) = (0, 0);
+        for url in self.urls.iter() {
+            if let Err(e) = self.webhook.post(url, notif) {
+                self.logger.lock().unwrap().log_failed_to_post_to_webhook(url, e);
+                match self.webhook.queue_retry(url, notif, Utc::now()) {
+                    Some(next_attempt_at) => {
+                        self.logger
+                            .lock()
+                            .unwrap()
+                            .log_webhook_post_queued_for_retry(url, next_attempt_at);
+                        n_sent += 1;
+                    }
+                    None => n_failed += 1,
+                }
+            } else {
+                self.logger.lock().unwrap().log_posted_to_webhook(url);
+                n_sent += 1;
+            }
+        }
+        delivery_result(self.name(), n_sent, n_failed)
+    }
+
+    /// Retries the failed requests that are due.
+    fn end_block_window(&mut self) {
+        let retried = self.webhook.retry_due(Utc::now());
+        self.log_retried(retried);
+    }
+
+    /// Makes a last attempt to send each failed request before `poagov` shuts down.
+    fn flush(&mut self) {
+        let retried = self.webhook.retry_all(Utc::now());
+        self.log_retried(retried);
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::Network;
+use crate::error::{Error, Result};
+use crate::state::write_atomically;
+
+/// Stores the number of the last block that `poagov` finished processing for a given network.
+///
+/// The checkpoint is only updated once every notification for a block-window has been sent, so
+/// restarting `poagov` with the `--resume` CLI flag will never skip blocks (though it may
+/// reprocess the block-window that `poagov` was working on when it was stopped).
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    path: PathBuf,
+}
+
+impl Checkpoint {
+    pub fn new(state_dir: &str, network: &Network) -> Self {
+        let file_name = format!("{}.checkpoint", network.id().to_lowercase());
+        let path = Path::new(state_dir).join(file_name);
+        Checkpoint { path }
+    }
+
+    pub fn path(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    /// Returns the last block number that was processed, returns `None` if no checkpoint has
+    /// been written for this network.
+    pub fn read(&self) -> Result<Option<u64>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::FailedToReadCheckpoint(e)),
+        };
+        match contents.trim().parse() {
+            Ok(block_number) => Ok(Some(block_number)),
+            Err(_) => Err(Error::InvalidCheckpoint(self.path())),
+        }
+    }
+
+    pub fn write(&self, block_number: u64) -> Result<()> {
+        if let Some(state_dir) = self.path.parent() {
+            fs::create_dir_all(state_dir).map_err(|e| Error::FailedToCreateStateDir(e))?;
+        }
+        let contents = format!("{}\n", block_number);
+        write_atomically(&self.path, contents.as_bytes())
+            .map_err(|e| Error::FailedToWriteCheckpoint(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::Checkpoint;
+    use crate::tests::SOKOL_NETWORK;
+
+    #[test]
+    fn test_write_then_read_checkpoint() {
+        let state_dir = env::temp_dir().join("poagov-test-checkpoint");
+        let _ = fs::remove_dir_all(&state_dir);
+        let checkpoint = Checkpoint::new(state_dir.to_str().unwrap(), &SOKOL_NETWORK);
+
+        assert_eq!(checkpoint.read().unwrap(), None);
+        checkpoint.write(6107511).unwrap();
+        assert_eq!(checkpoint.read().unwrap(), Some(6107511));
+        checkpoint.write(6107600).unwrap();
+        assert_eq!(checkpoint.read().unwrap(), Some(6107600));
+
+        fs::remove_dir_all(&state_dir).unwrap();
+    }
+}
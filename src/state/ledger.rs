@@ -0,0 +1,199 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr as _FromStr;
+
+use chrono::{DateTime, TimeZone as _TimeZone, Utc};
+use web3::types::{Address, U256};
+
+use crate::config::Network;
+use crate::error::{Error, Result};
+use crate::state::write_atomically;
+
+// The name of the file (within the state directory) that the ledger is stored in.
+const LEDGER_FILE_NAME: &str = "notified";
+
+/// Uniquely identifies a ballot event that `poagov` has sent a notification for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LedgerKey {
+    pub network: String,
+    pub contract: Address,
+    pub ballot_id: U256,
+    pub event: String,
+}
+
+impl LedgerKey {
+    pub fn new(network: &Network, contract: Address, ballot_id: U256, event: &str) -> Self {
+        LedgerKey {
+            network: network.id(),
+            contract,
+            ballot_id,
+            event: event.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LedgerEntry {
+    pub key: LedgerKey,
+    pub notified_at: DateTime<Utc>,
+}
+
+impl Display for LedgerEntry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "network: {}, contract: 0x{}, ballot_id: {}, event: {}, notified_at: {}",
+            self.key.network,
+            hex::encode(self.key.contract.0),
+            self.key.ballot_id,
+            self.key.event,
+            self.notified_at,
+        )
+    }
+}
+
+impl LedgerEntry {
+    /// Each entry is stored on its own line as:
+    /// "<network> <contract> <ballot_id> <timestamp> <event>".
+    fn to_line(&self) -> String {
+        format!(
+            "{} 0x{} {} {} {}\n",
+            self.key.network,
+            hex::encode(self.key.contract.0),
+            self.key.ballot_id,
+            self.notified_at.timestamp(),
+            self.key.event,
+        )
+    }
+
+    fn from_line(line: &str) -> Result<Self> {
+        let invalid_entry = || Error::InvalidLedgerEntry(line.to_string());
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(invalid_entry());
+        }
+        let network = fields[0].to_string();
+        let contract = Address::from_str(fields[1].trim_left_matches("0x"))
+            .map_err(|_| invalid_entry())?;
+        let ballot_id = U256::from_dec_str(fields[2]).map_err(|_| invalid_entry())?;
+        let timestamp: i64 = fields[3].parse().map_err(|_| invalid_entry())?;
+        let event = fields[4].to_string();
+        Ok(LedgerEntry {
+            key: LedgerKey { network, contract, ballot_id, event },
+            notified_at: Utc.timestamp(timestamp, 0),
+        })
+    }
+}
+
+/// A persistent record of every ballot that `poagov` has sent a notification for. The ledger is
+/// used to avoid notifying recipients about the same ballot more than once, e.g. when a
+/// `poagov` process is restarted using `--tail` or `--resume`.
+#[derive(Debug)]
+pub struct NotificationLedger {
+    path: PathBuf,
+    entries: Vec<LedgerEntry>,
+}
+
+impl NotificationLedger {
+    /// Loads the ledger from the state directory. If no ledger file exists, we return an empty
+    /// ledger.
+    pub fn open(state_dir: &str) -> Result<Self> {
+        let path = Path::new(state_dir).join(LEDGER_FILE_NAME);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(Error::FailedToReadLedger(e)),
+        };
+        let entries = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(LedgerEntry::from_line)
+            .collect::<Result<Vec<LedgerEntry>>>()?;
+        Ok(NotificationLedger { path, entries })
+    }
+
+    pub fn entries(&self) -> &[LedgerEntry] {
+        &self.entries
+    }
+
+    pub fn contains(&self, key: &LedgerKey) -> bool {
+        self.entries.iter().any(|entry| entry.key == *key)
+    }
+
+    /// Records that a notification has been sent for the ballot identified by `key`.
+    pub fn insert(&mut self, key: LedgerKey) -> Result<()> {
+        if !self.contains(&key) {
+            let notified_at = Utc::now();
+            self.entries.push(LedgerEntry { key, notified_at });
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Removes every entry that was notified prior to `cutoff`. Returns the number of entries
+    /// that were removed.
+    pub fn prune(&mut self, cutoff: DateTime<Utc>) -> Result<usize> {
+        let n_entries = self.entries.len();
+        self.entries.retain(|entry| entry.notified_at >= cutoff);
+        let n_pruned = n_entries - self.entries.len();
+        if n_pruned > 0 {
+            self.save()?;
+        }
+        Ok(n_pruned)
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(state_dir) = self.path.parent() {
+            fs::create_dir_all(state_dir).map_err(|e| Error::FailedToCreateStateDir(e))?;
+        }
+        let contents: String = self.entries.iter().map(LedgerEntry::to_line).collect();
+        write_atomically(&self.path, contents.as_bytes()).map_err(|e| Error::FailedToWriteLedger(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use chrono::{Duration, Utc};
+    use web3::types::{Address, U256};
+
+    use super::{LedgerKey, NotificationLedger};
+    use crate::tests::{CORE_NETWORK, SOKOL_NETWORK};
+
+    #[test]
+    fn test_ledger_persists_notified_ballots() {
+        let state_dir = env::temp_dir().join("poagov-test-ledger");
+        let _ = fs::remove_dir_all(&state_dir);
+        let state_dir = state_dir.to_str().unwrap();
+
+        let contract = Address::from(7);
+        let sokol_key = LedgerKey::new(&SOKOL_NETWORK, contract, U256::from(3), "BallotCreated");
+        let core_key = LedgerKey::new(&CORE_NETWORK, contract, U256::from(3), "BallotCreated");
+        let finalized_key =
+            LedgerKey::new(&SOKOL_NETWORK, contract, U256::from(3), "BallotFinalized");
+
+        let mut ledger = NotificationLedger::open(state_dir).unwrap();
+        assert!(!ledger.contains(&sokol_key));
+        ledger.insert(sokol_key.clone()).unwrap();
+        assert!(ledger.contains(&sokol_key));
+        assert!(!ledger.contains(&core_key));
+        assert!(!ledger.contains(&finalized_key));
+
+        // Reopening the ledger should load the previously inserted entry.
+        let mut ledger = NotificationLedger::open(state_dir).unwrap();
+        assert_eq!(ledger.entries().len(), 1);
+        assert!(ledger.contains(&sokol_key));
+
+        // Pruning with a cutoff in the past should not remove anything, pruning with a cutoff in
+        // the future should remove everything.
+        assert_eq!(ledger.prune(Utc::now() - Duration::days(1)).unwrap(), 0);
+        assert_eq!(ledger.prune(Utc::now() + Duration::days(1)).unwrap(), 1);
+        assert!(NotificationLedger::open(state_dir).unwrap().entries().is_empty());
+
+        fs::remove_dir_all(state_dir).unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+pub mod checkpoint;
+pub mod ledger;
+pub mod outbox;
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write as _Write};
+use std::path::{Path, PathBuf};
+
+/// Writes `contents` to the file at `path` such that a reader of `path` will only ever see the
+/// file's previous contents or the complete new `contents`, never a partial write (e.g. if
+/// `poagov` is killed while writing).
+///
+/// This is done by writing `contents` to a temporary file, flushing the temporary file to disk,
+/// then renaming the temporary file to `path`.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp_path = {
+        let mut tmp_path = OsString::from(path.as_os_str());
+        tmp_path.push(".tmp");
+        PathBuf::from(tmp_path)
+    };
+    {
+        let mut tmp_file = File::create(&tmp_path)?;
+        tmp_file.write_all(contents)?;
+        tmp_file.sync_all()?;
+    }
+    fs::rename(&tmp_path, path)
+}
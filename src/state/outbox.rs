@@ -0,0 +1,284 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write as _Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Utc};
+use serde_derive::{Deserialize, Serialize};
+use serde_json as json;
+
+use crate::error::{Error, Result};
+use crate::state::write_atomically;
+
+// The name of the file (within the state directory) that the outbox is stored in.
+const OUTBOX_FILE_NAME: &str = "outbox";
+
+// The name of the file (within the state directory) that emails are moved to once they have
+// failed to send `max_attempts` times.
+const DEAD_LETTER_FILE_NAME: &str = "outbox.dead";
+
+pub const DEFAULT_EMAIL_MAX_ATTEMPTS: u32 = 5;
+
+// The delay (in seconds) before the first retry, each subsequent retry doubles the delay.
+const INITIAL_RETRY_DELAY_SECS: i64 = 60;
+
+// The longest delay between two retries.
+const MAX_RETRY_DELAY_SECS: i64 = 24 * 60 * 60;
+
+/// A rendered email, stored in the outbox so that it can be resent without the notification that
+/// it was rendered from.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct OutgoingEmail {
+    pub recipient: String,
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+    pub high_priority: bool,
+}
+
+/// An email that failed to send and is waiting to be retried.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct OutboxEntry {
+    pub id: u64,
+    pub email: OutgoingEmail,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: String,
+}
+
+/// What happened to an email after a failed attempt to send it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Retry {
+    /// The email will be retried at the given time.
+    Scheduled(DateTime<Utc>),
+    /// The email reached the maximum number of attempts and was moved to the dead-letter file.
+    DeadLettered,
+}
+
+/// Returns the delay before the next attempt to send an email that has failed `attempts` times.
+fn retry_delay(attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(16);
+    let secs = (INITIAL_RETRY_DELAY_SECS << exponent).min(MAX_RETRY_DELAY_SECS);
+    Duration::seconds(secs)
+}
+
+/// A persistent queue of the emails that failed to send. Each entry is stored as a JSON object on
+/// its own line, so the outbox survives restarts. Emails that fail `max_attempts` times are
+/// appended to a dead-letter file, which `poagov` never reads.
+#[derive(Debug)]
+pub struct Outbox {
+    path: PathBuf,
+    dead_letter_path: PathBuf,
+    max_attempts: u32,
+    entries: Vec<OutboxEntry>,
+}
+
+impl Outbox {
+    /// Loads the outbox from the state directory. If no outbox file exists, we return an empty
+    /// outbox.
+    pub fn open(state_dir: &str, max_attempts: u32) -> Result<Self> {
+        let path = Path::new(state_dir).join(OUTBOX_FILE_NAME);
+        let dead_letter_path = Path::new(state_dir).join(DEAD_LETTER_FILE_NAME);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(Error::FailedToReadOutbox(e)),
+        };
+        let entries = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| json::from_str(line).map_err(|_| Error::InvalidOutboxEntry(line.into())))
+            .collect::<Result<Vec<OutboxEntry>>>()?;
+        Ok(Outbox {
+            path,
+            dead_letter_path,
+            max_attempts,
+            entries,
+        })
+    }
+
+    pub fn entries(&self) -> &[OutboxEntry] {
+        &self.entries
+    }
+
+    /// Returns the entries that are due to be retried at `now`.
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<OutboxEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.next_attempt_at <= now)
+            .cloned()
+            .collect()
+    }
+
+    /// Records the first failed attempt to send `email`.
+    pub fn push(&mut self, email: OutgoingEmail, e: &Error, now: DateTime<Utc>) -> Result<Retry> {
+        let id = self.entries.iter().map(|entry| entry.id + 1).max().unwrap_or(0);
+        let entry = OutboxEntry {
+            id,
+            email,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: String::new(),
+        };
+        self.record_failure(entry, e, now)
+    }
+
+    /// Records another failed attempt to send an entry returned by `due`.
+    pub fn retry_failed(
+        &mut self,
+        entry: OutboxEntry,
+        e: &Error,
+        now: DateTime<Utc>,
+    ) -> Result<Retry> {
+        self.entries.retain(|queued| queued.id != entry.id);
+        self.record_failure(entry, e, now)
+    }
+
+    /// Removes the entry `id` once it has been sent.
+    pub fn remove(&mut self, id: u64) -> Result<()> {
+        let n_entries = self.entries.len();
+        self.entries.retain(|entry| entry.id != id);
+        if self.entries.len() < n_entries {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn record_failure(
+        &mut self,
+        mut entry: OutboxEntry,
+        e: &Error,
+        now: DateTime<Utc>,
+    ) -> Result<Retry> {
+        entry.attempts += 1;
+        entry.last_error = format!("{:?}", e);
+        let retry = if entry.attempts >= self.max_attempts {
+            if let Err(write_err) = self.append_dead_letter(&entry) {
+                // Keep the email in the outbox rather than losing it, moving it to the dead-letter
+                // file is attempted again after its next failed attempt.
+                entry.next_attempt_at = now + retry_delay(entry.attempts);
+                self.entries.push(entry);
+                self.save()?;
+                return Err(write_err);
+            }
+            Retry::DeadLettered
+        } else {
+            entry.next_attempt_at = now + retry_delay(entry.attempts);
+            let next_attempt_at = entry.next_attempt_at;
+            self.entries.push(entry);
+            Retry::Scheduled(next_attempt_at)
+        };
+        self.save()?;
+        Ok(retry)
+    }
+
+    fn append_dead_letter(&self, entry: &OutboxEntry) -> Result<()> {
+        self.create_state_dir()?;
+        let line = format!("{}\n", json::to_string(entry).unwrap());
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.dead_letter_path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| Error::FailedToWriteOutbox(e))
+    }
+
+    fn create_state_dir(&self) -> Result<()> {
+        if let Some(state_dir) = self.path.parent() {
+            fs::create_dir_all(state_dir).map_err(|e| Error::FailedToCreateStateDir(e))?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<()> {
+        self.create_state_dir()?;
+        let contents: String = self
+            .entries
+            .iter()
+            .map(|entry| format!("{}\n", json::to_string(entry).unwrap()))
+            .collect();
+        write_atomically(&self.path, contents.as_bytes()).map_err(|e| Error::FailedToWriteOutbox(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::Path;
+
+    use chrono::{Duration, Utc};
+
+    use super::{Outbox, OutgoingEmail, Retry, DEAD_LETTER_FILE_NAME};
+    use crate::error::Error;
+
+    fn email(recipient: &str) -> OutgoingEmail {
+        OutgoingEmail {
+            recipient: recipient.to_string(),
+            subject: "POA Network Governance Notification (Sokol)".to_string(),
+            text: "Memo: lower the threshold\n".to_string(),
+            html: "<html></html>\n".to_string(),
+            high_priority: false,
+        }
+    }
+
+    #[test]
+    fn test_outbox_retries_and_dead_letters() {
+        let state_dir = env::temp_dir().join("poagov-test-outbox");
+        let _ = fs::remove_dir_all(&state_dir);
+        let state_dir = state_dir.to_str().unwrap();
+        let e = Error::InvalidWebhookResponse(500);
+        let now = Utc::now();
+
+        let mut outbox = Outbox::open(state_dir, 3).unwrap();
+        let retry = outbox.push(email("alice@poa.network"), &e, now).unwrap();
+        assert_eq!(retry, Retry::Scheduled(now + Duration::minutes(1)));
+        outbox.push(email("bob@poa.network"), &e, now).unwrap();
+        assert!(outbox.due(now).is_empty());
+        assert_eq!(outbox.due(now + Duration::minutes(1)).len(), 2);
+
+        // Reopening the outbox should load the previously failed emails.
+        let mut outbox = Outbox::open(state_dir, 3).unwrap();
+        assert_eq!(outbox.entries().len(), 2);
+        let alice = outbox.entries()[0].clone();
+        let bob = outbox.entries()[1].clone();
+        assert_eq!(alice.email, email("alice@poa.network"));
+        assert_ne!(alice.id, bob.id);
+
+        let later = now + Duration::minutes(1);
+        let retry = outbox.retry_failed(alice.clone(), &e, later).unwrap();
+        assert_eq!(retry, Retry::Scheduled(later + Duration::minutes(2)));
+        let alice = outbox.entries()[1].clone();
+        assert_eq!(alice.attempts, 2);
+        assert_eq!(outbox.retry_failed(alice, &e, later).unwrap(), Retry::DeadLettered);
+        outbox.remove(bob.id).unwrap();
+        assert!(outbox.entries().is_empty());
+        assert!(Outbox::open(state_dir, 3).unwrap().entries().is_empty());
+
+        let dead_letter_path = Path::new(state_dir).join(DEAD_LETTER_FILE_NAME);
+        let dead_letters = fs::read_to_string(dead_letter_path).unwrap();
+        assert_eq!(dead_letters.lines().count(), 1);
+        assert!(dead_letters.contains("alice@poa.network"));
+        assert!(dead_letters.contains(r#""attempts":3"#));
+    }
+
+    #[test]
+    fn test_outbox_keeps_email_if_dead_letter_fails() {
+        let state_dir = env::temp_dir().join("poagov-test-outbox-dead-letter");
+        let _ = fs::remove_dir_all(&state_dir);
+        let state_dir = state_dir.to_str().unwrap();
+        let e = Error::InvalidWebhookResponse(500);
+        let now = Utc::now();
+
+        // The dead-letter file cannot be opened for writing when its path is a directory.
+        fs::create_dir_all(Path::new(state_dir).join(DEAD_LETTER_FILE_NAME)).unwrap();
+        let mut outbox = Outbox::open(state_dir, 2).unwrap();
+        outbox.push(email("alice@poa.network"), &e, now).unwrap();
+        let alice = outbox.entries()[0].clone();
+        assert!(outbox.retry_failed(alice, &e, now).is_err());
+
+        let outbox = Outbox::open(state_dir, 2).unwrap();
+        assert_eq!(outbox.entries().len(), 1);
+        assert_eq!(outbox.entries()[0].email, email("alice@poa.network"));
+        assert_eq!(outbox.entries()[0].attempts, 2);
+    }
+}
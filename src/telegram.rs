@@ -0,0 +1,124 @@
+use serde_json as json;
+
+use crate::error::{Error, Result};
+use crate::notify::Notification;
+use crate::watch::{alert_prefix, WatchedAddress};
+
+pub const DEFAULT_TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+/// Escapes the characters that have a special meaning in Telegram's (legacy) Markdown.
+fn escape_markdown(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if let '_' | '*' | '`' | '[' = c {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Formats a notification as a Telegram Markdown message. Each "<field>: <value>" line of the
+/// notification's email text is formatted with the field name in bold. Messages for ballots that
+/// contain an address in `watch_list` are prefixed with `WATCH_LIST_SUBJECT_PREFIX`.
+pub fn format_markdown(notif: &Notification, watch_list: &[WatchedAddress]) -> String {
+    let mut lines = vec![format!(
+        "*{}POA Network Governance Notification: {}*",
+        escape_markdown(&alert_prefix(watch_list, &notif.ballot_state())),
+        escape_markdown(notif.event()),
+    )];
+    for line in notif.email_text().lines().filter(|line| !line.trim().is_empty()) {
+        let line = match line.find(": ") {
+            Some(i) => format!(
+                "*{}:* {}",
+                escape_markdown(&line[..i]),
+                escape_markdown(&line[i + 2..]),
+            ),
+            None => escape_markdown(line),
+        };
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Sends notifications to Telegram chats using the Telegram Bot API's `sendMessage` method.
+#[derive(Debug)]
+pub struct TelegramBot {
+    client: reqwest::Client,
+    api_url: String,
+    bot_token: String,
+    watch_list: Vec<WatchedAddress>,
+}
+
+impl TelegramBot {
+    pub fn new(api_url: &str, bot_token: &str, watch_list: Vec<WatchedAddress>) -> Self {
+        TelegramBot {
+            client: reqwest::Client::new(),
+            api_url: api_url.trim_right_matches('/').to_string(),
+            bot_token: bot_token.to_string(),
+            watch_list,
+        }
+    }
+
+    pub fn send(&self, chat_id: &str, notif: &Notification) -> Result<()> {
+        let url = format!("{}/bot{}/sendMessage", self.api_url, self.bot_token);
+        let payload = json::json!({
+            "chat_id": chat_id,
+            "text": format_markdown(notif, &self.watch_list),
+            "parse_mode": "Markdown",
+            "disable_web_page_preview": true,
+        });
+        let mut resp = self
+            .client
+            .post(&url)
+            .json(&payload)
+            .send()
+            .map_err(Error::FailedToSendTelegramMessage)?;
+        // The Bot API responds with `{"ok": false, "description": "..."}` when a request fails.
+        let body: json::Value = resp.json().unwrap_or(json::Value::Null);
+        if resp.status().is_success() && body["ok"] == json::Value::Bool(true) {
+            Ok(())
+        } else {
+            let description = match body["description"].as_str() {
+                Some(description) => description.to_string(),
+                None => format!("HTTP status {}", resp.status().as_u16()),
+            };
+            Err(Error::TelegramApiError(description))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use web3::types::Address;
+
+    use super::{escape_markdown, format_markdown, TelegramBot};
+    use crate::tests::{sokol_network_config, stand_in_server, threshold_ballot_created};
+    use crate::watch::WatchedAddress;
+
+    #[test]
+    fn test_send_notification_to_telegram() {
+        let config = sokol_network_config();
+        let notif = threshold_ballot_created(&config);
+
+        let (api_url, rx) = stand_in_server("200 OK", r#"{"ok":true,"result":{}}"#);
+        TelegramBot::new(&api_url, "123:abc", vec![]).send("-1001", &notif).unwrap();
+        let request = rx.recv().unwrap();
+        assert!(request.starts_with("POST /bot123:abc/sendMessage"));
+        assert!(request.contains(r#""chat_id":"-1001""#));
+        assert!(request.contains(r#""parse_mode":"Markdown""#));
+        assert!(request.contains("*Network:* Sokol"));
+        assert!(request.contains("*Memo:* lower the threshold"));
+
+        let error_body = r#"{"ok":false,"description":"Bad Request: chat not found"}"#;
+        let (api_url, _rx) = stand_in_server("400 Bad Request", error_body);
+        assert!(TelegramBot::new(&api_url, "123:abc", vec![]).send("-1001", &notif).is_err());
+
+        assert_eq!(escape_markdown("a_b*c`d[e"), "a\\_b\\*c\\`d\\[e");
+
+        // The ballot's creator is the zero address.
+        let watched = WatchedAddress { addr: Address::zero(), label: None };
+        let message = format_markdown(&notif, &[watched]);
+        assert!(message.starts_with("*\\[WATCH-LIST ALERT] POA Network Governance Notification"));
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::fs;
+
+use handlebars::Handlebars;
+use serde_json as json;
+
+use crate::config::EmailTemplateConfig;
+use crate::error::{Error, Result};
+use crate::notify::Notification;
+use crate::watch::WatchedAddress;
+
+/// The subject used when no subject template is configured, matches the subject of the emails
+/// sent before templates were added.
+pub const DEFAULT_SUBJECT_TEMPLATE: &str = "POA Network Governance Notification ({{network}})";
+
+/// The body used when no body template is configured, `text` is the notification's plain-text
+/// email body (see `Notification::email_text`).
+pub const DEFAULT_BODY_TEMPLATE: &str = "{{text}}";
+
+/// The names of the ballot types that per-ballot-type templates can be given for.
+const BALLOT_TYPE_NAMES: &[&str] = &[
+    "InvalidKey",
+    "AddKey",
+    "RemoveKey",
+    "SwapKey",
+    "Threshold",
+    "Proxy",
+    "Emission",
+];
+
+/// Builds the data that email templates are rendered with.
+///
+/// The ballot id and block number are formatted as decimal strings, `ballot` and `details`
+/// contain every decoded field of the ballot and event (as formatted by `Notification::to_json`),
+/// and `notification` contains the notification's entire JSON document (including the addresses
+/// in `watch_list` that were found in the ballot).
+pub fn template_data(notif: &Notification, watch_list: &[WatchedAddress]) -> json::Value {
+    let document = notif.to_json(watch_list);
+    let ballot_state = notif.ballot_state();
+    let end_time = ballot_state.end_time();
+    json::json!({
+        "network": notif.network().name(),
+        "event": notif.event(),
+        "contract_name": notif.contract_name(),
+        "contract_version": format!("{:?}", notif.contract().version),
+        "ballot_id": notif.ballot_id().to_string(),
+        "block_number": notif.block_number().to_string(),
+        "ballot_type": format!("{:?}", notif.ballot_type()),
+        "end_time": end_time.to_string(),
+        "end_date": end_time.format("%Y-%m-%d").to_string(),
+        "memo": ballot_state.memo(),
+        "text": notif.email_text(),
+        "ballot": document["ballot"],
+        "details": document["details"],
+        "notification": document,
+    })
+}
+
+fn check_ballot_type_names(templates: &HashMap<String, String>) -> Result<()> {
+    for ballot_type in templates.keys() {
+        if !BALLOT_TYPE_NAMES.contains(&ballot_type.as_str()) {
+            let msg = format!("unknown ballot type: {}", ballot_type);
+            return Err(Error::InvalidEmailTemplate(msg));
+        }
+    }
+    Ok(())
+}
+
+fn read_template_file(path: &str) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| Error::FailedToReadEmailTemplate(path.to_string(), e))
+}
+
+/// Renders the subject and body of each notification email using Handlebars templates.
+///
+/// A subject or body template given for a ballot type (e.g. "AddKey") is used for that ballot type
+/// instead of the default subject or body template.
+pub struct EmailTemplates {
+    registry: Handlebars,
+}
+
+impl EmailTemplates {
+    /// Reads and compiles every configured template, returns an error if a template file cannot
+    /// be read or a template is invalid.
+    pub fn new(config: &EmailTemplateConfig) -> Result<Self> {
+        check_ballot_type_names(&config.subjects)?;
+        check_ballot_type_names(&config.body_files)?;
+        let mut registry = Handlebars::new();
+        // Emails are sent as plain text, so rendered values are not HTML escaped.
+        registry.register_escape_fn(handlebars::no_escape);
+
+        let subject = config
+            .subject
+            .clone()
+            .unwrap_or_else(|| DEFAULT_SUBJECT_TEMPLATE.to_string());
+        let body = match config.body_file {
+            Some(ref path) => read_template_file(path)?,
+            None => DEFAULT_BODY_TEMPLATE.to_string(),
+        };
+        let mut templates = vec![("subject".to_string(), subject), ("body".to_string(), body)];
+        for (ballot_type, subject) in config.subjects.iter() {
+            templates.push((format!("subject:{}", ballot_type), subject.clone()));
+        }
+        for (ballot_type, path) in config.body_files.iter() {
+            templates.push((format!("body:{}", ballot_type), read_template_file(path)?));
+        }
+        for (name, template) in templates {
+            registry
+                .register_template_string(&name, template)
+                .map_err(|e| Error::InvalidEmailTemplate(format!("{}: {}", name, e)))?;
+        }
+        Ok(EmailTemplates { registry })
+    }
+
+    pub fn subject(&self, notif: &Notification, watch_list: &[WatchedAddress]) -> Result<String> {
+        let subject = self.render("subject", notif, watch_list)?;
+        // Email subjects cannot contain line breaks.
+        Ok(subject.lines().map(str::trim).collect::<Vec<&str>>().join(" "))
+    }
+
+    pub fn body(&self, notif: &Notification, watch_list: &[WatchedAddress]) -> Result<String> {
+        self.render("body", notif, watch_list)
+    }
+
+    fn render(
+        &self,
+        kind: &str,
+        notif: &Notification,
+        watch_list: &[WatchedAddress],
+    ) -> Result<String> {
+        let ballot_type_template = format!("{}:{:?}", kind, notif.ballot_type());
+        let name = if self.registry.has_template(&ballot_type_template) {
+            ballot_type_template.as_str()
+        } else {
+            kind
+        };
+        self.registry
+            .render(name, &template_data(notif, watch_list))
+            .map_err(|e| Error::FailedToRenderEmailTemplate(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::EmailTemplates;
+    use crate::config::EmailTemplateConfig;
+    use crate::tests::{sokol_network_config, threshold_ballot_created};
+
+    #[test]
+    fn test_default_templates_match_email_text() {
+        let config = sokol_network_config();
+        let notif = threshold_ballot_created(&config);
+        let templates = EmailTemplates::new(&EmailTemplateConfig::default()).unwrap();
+        assert_eq!(
+            templates.subject(&notif, &[]).unwrap(),
+            "POA Network Governance Notification (Sokol)",
+        );
+        assert_eq!(templates.body(&notif, &[]).unwrap(), notif.email_text());
+    }
+
+    #[test]
+    fn test_per_ballot_type_subject() {
+        let config = sokol_network_config();
+        let notif = threshold_ballot_created(&config);
+        let mut subjects = HashMap::new();
+        subjects.insert(
+            "Threshold".to_string(),
+            "[{{network}}] New {{ballot_type}} ballot #{{ballot_id}} ends {{end_date}}".to_string(),
+        );
+        subjects.insert("AddKey".to_string(), "unused".to_string());
+        let template_config = EmailTemplateConfig {
+            subject: Some("{{event}} & {{ballot.memo}}".to_string()),
+            subjects,
+            ..Default::default()
+        };
+        let templates = EmailTemplates::new(&template_config).unwrap();
+        let subject = templates.subject(&notif, &[]).unwrap();
+        assert!(subject.starts_with("[Sokol] New Threshold ballot #12 ends "));
+
+        let template_config = EmailTemplateConfig {
+            subject: Some("{{event}} & {{ballot.memo}}".to_string()),
+            ..Default::default()
+        };
+        let templates = EmailTemplates::new(&template_config).unwrap();
+        assert_eq!(
+            templates.subject(&notif, &[]).unwrap(),
+            "BallotCreated & lower the threshold",
+        );
+
+        let mut subjects = HashMap::new();
+        subjects.insert("NotABallotType".to_string(), "{{network}}".to_string());
+        let template_config = EmailTemplateConfig { subjects, ..Default::default() };
+        assert!(EmailTemplates::new(&template_config).is_err());
+
+        let template_config = EmailTemplateConfig {
+            subject: Some("{{#if}}".to_string()),
+            ..Default::default()
+        };
+        assert!(EmailTemplates::new(&template_config).is_err());
+    }
+}
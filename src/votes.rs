@@ -0,0 +1,136 @@
+use serde_derive::Serialize;
+use web3::types::U256;
+
+use crate::config::ContractType;
+use crate::response::common::BallotState;
+use crate::response::v1::VotingState;
+use crate::response::v2::BallotInfo;
+
+/// The number of votes for each choice that have been cast on a single ballot.
+///
+/// The tally is read from the ballot's state in the contract (rather than counted from the `Vote`
+/// events that `poagov` has seen), so it includes every vote cast on the ballot up to the latest
+/// block, not only the votes cast up to the block of the vote being notified.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct VoteTally {
+    pub accept: u64,
+    pub reject: u64,
+    pub send: u64,
+    pub burn: u64,
+    pub freeze: u64,
+}
+
+impl VoteTally {
+    pub fn from_ballot_state(ballot_state: &BallotState) -> Self {
+        match ballot_state {
+            BallotState::VotingState(VotingState::Keys(state)) => {
+                VoteTally::from_progress(state.total_voters, state.progress)
+            }
+            BallotState::VotingState(VotingState::Threshold(state)) => {
+                VoteTally::from_progress(state.total_voters, state.progress)
+            }
+            BallotState::VotingState(VotingState::Proxy(state)) => {
+                VoteTally::from_progress(state.total_voters, state.progress)
+            }
+            BallotState::BallotInfo(BallotInfo::Keys(info)) => {
+                VoteTally::from_progress(info.total_voters, info.progress)
+            }
+            BallotState::BallotInfo(BallotInfo::Threshold(info)) => {
+                VoteTally::from_progress(info.total_voters, info.progress)
+            }
+            BallotState::BallotInfo(BallotInfo::Proxy(info)) => {
+                VoteTally::from_progress(info.total_voters, info.progress)
+            }
+            BallotState::BallotInfo(BallotInfo::Emission(info)) => VoteTally {
+                send: info.send_votes.low_u64(),
+                burn: info.burn_votes.low_u64(),
+                freeze: info.freeze_votes.low_u64(),
+                ..VoteTally::default()
+            },
+        }
+    }
+
+    /// The Keys, Threshold, and Proxy contracts do not store the number of votes for each choice,
+    /// they store the number of voters and the ballot's progress (the number of votes for minus
+    /// the number of votes against). The progress is an `int256`, so a negative progress is
+    /// stored in two's complement.
+    fn from_progress(total_voters: U256, progress: U256) -> Self {
+        let total_voters = total_voters.low_u64() as i64;
+        let progress = if progress.bit(255) {
+            -((U256::max_value() - progress + U256::one()).low_u64() as i64)
+        } else {
+            progress.low_u64() as i64
+        };
+        VoteTally {
+            accept: ((total_voters + progress) / 2).max(0) as u64,
+            reject: ((total_voters - progress) / 2).max(0) as u64,
+            ..VoteTally::default()
+        }
+    }
+
+    pub fn email_text(&self, contract_type: ContractType) -> String {
+        if contract_type == ContractType::Emission {
+            format!(
+                "Current Tally: {} send, {} burn, {} freeze\n",
+                self.send, self.burn, self.freeze
+            )
+        } else {
+            format!("Current Tally: {} for, {} against\n", self.accept, self.reject)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use web3::types::{Address, U256};
+
+    use super::VoteTally;
+    use crate::response::v2::{BallotInfo, EmissionBallotInfo, ThresholdBallotInfo};
+
+    fn threshold_ballot(total_voters: u64, progress: U256) -> BallotInfo {
+        BallotInfo::Threshold(ThresholdBallotInfo {
+            start_time: Utc::now(),
+            end_time: Utc::now(),
+            total_voters: U256::from(total_voters),
+            progress,
+            is_finalized: false,
+            proposed_value: U256::from(3),
+            creator: Address::zero(),
+            memo: "lower the threshold".to_string(),
+            can_be_finalized_now: false,
+            already_voted: false,
+        })
+    }
+
+    #[test]
+    fn test_tally_from_ballot_state() {
+        // 3 voters with a progress of +1: 2 for, 1 against.
+        let ballot = threshold_ballot(3, U256::from(1));
+        let tally = VoteTally::from_ballot_state(&ballot.into());
+        assert_eq!(tally, VoteTally { accept: 2, reject: 1, ..VoteTally::default() });
+
+        // 5 voters with a progress of -3 (stored in two's complement): 1 for, 4 against.
+        let minus_three = U256::max_value() - U256::from(2);
+        let ballot = threshold_ballot(5, minus_three);
+        let tally = VoteTally::from_ballot_state(&ballot.into());
+        assert_eq!(tally, VoteTally { accept: 1, reject: 4, ..VoteTally::default() });
+
+        let ballot = BallotInfo::Emission(EmissionBallotInfo {
+            creation_time: Utc::now(),
+            start_time: Utc::now(),
+            end_time: Utc::now(),
+            is_canceled: false,
+            is_finalized: false,
+            creator: Address::zero(),
+            memo: "send funds".to_string(),
+            amount: U256::from(100),
+            burn_votes: U256::from(1),
+            freeze_votes: U256::from(0),
+            send_votes: U256::from(2),
+            receiver: Address::zero(),
+        });
+        let tally = VoteTally::from_ballot_state(&ballot.into());
+        assert_eq!(tally, VoteTally { send: 2, burn: 1, ..VoteTally::default() });
+    }
+}
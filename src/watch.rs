@@ -0,0 +1,157 @@
+use std::str::FromStr as _FromStr;
+
+use serde_json as json;
+use web3::types::Address;
+
+use crate::error::{Error, Result};
+use crate::response::common::BallotState;
+
+/// Prepended to the subject of emails for ballots that contain a watched address.
+pub const WATCH_LIST_SUBJECT_PREFIX: &str = "[WATCH-LIST ALERT]";
+
+/// An address that we want to be alerted about whenever it appears in a ballot (e.g. our own mining
+/// key or an emission funds receiver).
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchedAddress {
+    pub addr: Address,
+    pub label: Option<String>,
+}
+
+impl WatchedAddress {
+    /// Parses a watched address from the `WATCH_LIST` env-var, each watched address is formatted
+    /// as either "<address>" or "<address>:<label>".
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut split = s.trim().splitn(2, ':');
+        let addr_str = split.next().unwrap();
+        let addr = Address::from_str(addr_str.trim_left_matches("0x"))
+            .map_err(|_| Error::InvalidWatchedAddress(addr_str.to_string()))?;
+        let label = split
+            .next()
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty());
+        Ok(WatchedAddress { addr, label })
+    }
+}
+
+/// A watched address that was found in one of a ballot's address fields.
+#[derive(Clone, Debug)]
+pub struct WatchListMatch<'a> {
+    pub watched: &'a WatchedAddress,
+    /// The name of the ballot field containing the address, e.g. "affected_key".
+    pub field: &'static str,
+}
+
+impl<'a> WatchListMatch<'a> {
+    pub fn alert_text(&self) -> String {
+        let addr = format!("{:?}", self.watched.addr);
+        let watched = match self.watched.label {
+            Some(ref label) => format!("{} ({})", label, addr),
+            None => addr,
+        };
+        format!("Watch-List Alert: {} is the ballot's {}", watched, self.field)
+    }
+
+    pub fn to_json(&self) -> json::Value {
+        json::json!({
+            "address": self.watched.addr,
+            "label": self.watched.label,
+            "field": self.field,
+        })
+    }
+}
+
+/// Returns each watched address found in the ballot's address fields.
+pub fn find_matches<'a>(
+    watch_list: &'a [WatchedAddress],
+    ballot_state: &BallotState,
+) -> Vec<WatchListMatch<'a>> {
+    let mut matches = vec![];
+    for (field, addr) in ballot_state.addresses() {
+        for watched in watch_list.iter().filter(|watched| watched.addr == addr) {
+            matches.push(WatchListMatch { watched, field });
+        }
+    }
+    matches
+}
+
+/// Returns the prefix (`WATCH_LIST_SUBJECT_PREFIX` followed by a space) prepended to the heading
+/// of chat and Telegram messages for ballots that contain a watched address, otherwise returns an
+/// empty string.
+pub fn alert_prefix(watch_list: &[WatchedAddress], ballot_state: &BallotState) -> String {
+    if find_matches(watch_list, ballot_state).is_empty() {
+        String::new()
+    } else {
+        format!("{} ", WATCH_LIST_SUBJECT_PREFIX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use web3::types::{Address, U256};
+
+    use super::{find_matches, WatchedAddress};
+    use crate::response::common::{BallotState, BallotType, KeyType};
+    use crate::response::v2::{BallotInfo, EmissionBallotInfo, KeysBallotInfo};
+
+    #[test]
+    fn test_watch_list_matches() {
+        let mining_key =
+            WatchedAddress::parse("0x0000000000000000000000000000000000000007:our mining key")
+                .unwrap();
+        assert_eq!(mining_key.addr, Address::from(7));
+        assert_eq!(mining_key.label, Some("our mining key".to_string()));
+        let receiver =
+            WatchedAddress::parse(" 0x0000000000000000000000000000000000000009").unwrap();
+        assert!(receiver.label.is_none());
+        assert!(WatchedAddress::parse("0xnotanaddress:label").is_err());
+        let watch_list = vec![mining_key, receiver];
+
+        let now = Utc::now();
+        let keys_ballot: BallotState = BallotInfo::Keys(KeysBallotInfo {
+            start_time: now,
+            end_time: now,
+            affected_key: Address::from(7),
+            affected_key_type: KeyType::MiningKey,
+            new_voting_key: Address::zero(),
+            new_payout_key: Address::zero(),
+            mining_key: Address::zero(),
+            total_voters: U256::from(0),
+            progress: U256::from(0),
+            is_finalized: false,
+            ballot_type: BallotType::RemoveKey,
+            creator: Address::from(1),
+            memo: "remove our key".to_string(),
+            can_be_finalized_now: false,
+        })
+        .into();
+        let matches = find_matches(&watch_list, &keys_ballot);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            matches[0].alert_text(),
+            "Watch-List Alert: our mining key (0x0000000000000000000000000000000000000007) is the \
+             ballot's affected_key",
+        );
+
+        let emission_ballot: BallotState = BallotInfo::Emission(EmissionBallotInfo {
+            creation_time: now,
+            start_time: now,
+            end_time: now,
+            is_canceled: false,
+            is_finalized: false,
+            creator: Address::from(1),
+            memo: "send funds".to_string(),
+            amount: U256::from(100),
+            burn_votes: U256::from(0),
+            freeze_votes: U256::from(0),
+            send_votes: U256::from(0),
+            receiver: Address::from(9),
+        })
+        .into();
+        let matches = find_matches(&watch_list, &emission_ballot);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].field, "receiver");
+        assert!(matches[0].watched.label.is_none());
+        assert!(find_matches(&watch_list[1..], &keys_ballot).is_empty());
+    }
+}
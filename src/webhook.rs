@@ -0,0 +1,264 @@
+use chrono::{DateTime, Duration, Utc};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use reqwest::header::{ContentType, Headers};
+
+use crate::error::{Error, Result};
+use crate::notify::Notification;
+use crate::watch::WatchedAddress;
+
+/// The header containing the hex encoded HMAC-SHA256 of the request body, formatted as
+/// "sha256=<hex>".
+pub const SIGNATURE_HEADER: &str = "X-Poagov-Signature";
+
+/// The header containing the notification's event name.
+pub const EVENT_HEADER: &str = "X-Poagov-Event";
+
+pub const DEFAULT_WEBHOOK_MAX_RETRIES: u32 = 3;
+
+/// The delay (in milliseconds) before the first retry, each subsequent retry doubles the delay.
+const INITIAL_BACKOFF_MS: i64 = 1000;
+
+/// Returns the hex encoded HMAC-SHA256 of `body` using the key `secret`.
+pub fn sign(secret: &str, body: &[u8]) -> Result<String> {
+    let key = PKey::hmac(secret.as_bytes()).map_err(Error::FailedToSignWebhookPayload)?;
+    let mut signer =
+        Signer::new(MessageDigest::sha256(), &key).map_err(Error::FailedToSignWebhookPayload)?;
+    signer.update(body).map_err(Error::FailedToSignWebhookPayload)?;
+    let hmac = signer.sign_to_vec().map_err(Error::FailedToSignWebhookPayload)?;
+    Ok(hex::encode(hmac))
+}
+
+/// A request that failed and is waiting to be retried.
+#[derive(Clone, Debug)]
+struct PendingPost {
+    url: String,
+    event: &'static str,
+    body: String,
+    retries: u32,
+    next_attempt_at: DateTime<Utc>,
+}
+
+/// The outcome of retrying a request that failed.
+#[derive(Debug)]
+pub struct RetriedPost {
+    pub url: String,
+    pub result: Result<()>,
+    /// The time of the next retry, `None` if the request was sent or if it has been retried
+    /// `max_retries` times.
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
+
+/// Posts each notification's JSON document (see `Notification::to_json`) to HTTP webhooks.
+/// Requests that fail or receive a non-2xx response are queued and retried with exponential
+/// backoff by `retry_due`, so a failing webhook never blocks the notifications that follow it.
+#[derive(Debug)]
+pub struct JsonWebhook {
+    client: reqwest::Client,
+    secret: String,
+    max_retries: u32,
+    initial_backoff: Duration,
+    watch_list: Vec<WatchedAddress>,
+    pending: Vec<PendingPost>,
+}
+
+impl JsonWebhook {
+    pub fn new(secret: &str, max_retries: u32, watch_list: Vec<WatchedAddress>) -> Self {
+        JsonWebhook {
+            client: reqwest::Client::new(),
+            secret: secret.to_string(),
+            max_retries,
+            initial_backoff: Duration::milliseconds(INITIAL_BACKOFF_MS),
+            watch_list,
+            pending: vec![],
+        }
+    }
+
+    /// Makes a single attempt to post the notification's JSON document to `url`.
+    pub fn post(&self, url: &str, notif: &Notification) -> Result<()> {
+        let body = notif.to_json(&self.watch_list).to_string();
+        self.post_body(url, notif.event(), &body)
+    }
+
+    fn post_body(&self, url: &str, event: &str, body: &str) -> Result<()> {
+        let signature = sign(&self.secret, body.as_bytes())?;
+        let mut headers = Headers::new();
+        headers.set(ContentType::json());
+        headers.set_raw(SIGNATURE_HEADER, format!("sha256={}", signature));
+        headers.set_raw(EVENT_HEADER, event);
+        let resp = self
+            .client
+            .post(url)
+            .headers(headers)
+            .body(body.to_string())
+            .send()
+            .map_err(Error::FailedToPostToWebhook)?;
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(Error::InvalidWebhookResponse(resp.status().as_u16()))
+        }
+    }
+
+    /// Queues a notification that failed to post to `url`. Returns the time of the first retry,
+    /// or `None` if retries are disabled (`max_retries` is 0).
+    pub fn queue_retry(
+        &mut self,
+        url: &str,
+        notif: &Notification,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        if self.max_retries == 0 {
+            return None;
+        }
+        let next_attempt_at = now + self.initial_backoff;
+        self.pending.push(PendingPost {
+            url: url.to_string(),
+            event: notif.event(),
+            body: notif.to_json(&self.watch_list).to_string(),
+            retries: 0,
+            next_attempt_at,
+        });
+        Some(next_attempt_at)
+    }
+
+    /// Retries each queued request that is due at `now`.
+    pub fn retry_due(&mut self, now: DateTime<Utc>) -> Vec<RetriedPost> {
+        self.retry(now, |post| post.next_attempt_at <= now)
+    }
+
+    /// Retries every queued request, whether or not it is due (used when `poagov` shuts down).
+    pub fn retry_all(&mut self, now: DateTime<Utc>) -> Vec<RetriedPost> {
+        self.retry(now, |_| true)
+    }
+
+    fn retry<F>(&mut self, now: DateTime<Utc>, filter: F) -> Vec<RetriedPost>
+    where
+        F: Fn(&PendingPost) -> bool,
+    {
+        let (due, not_due): (Vec<PendingPost>, Vec<PendingPost>) =
+            self.pending.drain(..).partition(|post| filter(post));
+        self.pending = not_due;
+        let mut retried = vec![];
+        for mut post in due {
+            let result = self.post_body(&post.url, post.event, &post.body);
+            post.retries += 1;
+            let mut next_attempt_at = None;
+            if result.is_err() && post.retries < self.max_retries {
+                // Each retry doubles the delay before the next retry.
+                let exponent = post.retries.min(16);
+                post.next_attempt_at = now + self.initial_backoff * (1 << exponent);
+                next_attempt_at = Some(post.next_attempt_at);
+                self.pending.push(post.clone());
+            }
+            retried.push(RetriedPost {
+                url: post.url,
+                result,
+                next_attempt_at,
+            });
+        }
+        retried
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+    use web3::types::Address;
+
+    use super::{sign, JsonWebhook, SIGNATURE_HEADER};
+    use crate::tests::{sokol_network_config, stand_in_server, threshold_ballot_created};
+    use crate::watch::WatchedAddress;
+
+    #[test]
+    fn test_webhook_json_document() {
+        let config = sokol_network_config();
+        let notif = threshold_ballot_created(&config);
+        let doc = notif.to_json(&[]);
+        assert_eq!(doc["event"], "BallotCreated");
+        assert_eq!(doc["network"], "Sokol");
+        assert_eq!(doc["contract"]["kind"], "threshold");
+        assert_eq!(doc["contract"]["version"], "v2");
+        assert_eq!(doc["ballot_type"], "Threshold");
+        assert_eq!(doc["ballot"]["contract"], "Threshold");
+        assert_eq!(doc["ballot"]["memo"], "lower the threshold");
+        assert!(doc["ballot"]["can_be_finalized_now"].is_boolean());
+        assert!(doc["details"]["creator"].is_string());
+        assert_eq!(doc["details"]["log"]["ballot_id"], "0xc");
+        assert_eq!(doc["watch_list_matches"], json!([]));
+
+        // The ballot's creator is the zero address.
+        let watched = WatchedAddress { addr: Address::zero(), label: Some("creator".to_string()) };
+        let doc = notif.to_json(&[watched]);
+        assert_eq!(
+            doc["watch_list_matches"],
+            json!([{
+                "address": "0x0000000000000000000000000000000000000000",
+                "label": "creator",
+                "field": "creator",
+            }]),
+        );
+    }
+
+    #[test]
+    fn test_post_signed_notification() {
+        let config = sokol_network_config();
+        let notif = threshold_ballot_created(&config);
+        let webhook = JsonWebhook::new("secret", 1, vec![]);
+
+        let (url, rx) = stand_in_server("200 OK", "");
+        webhook.post(&url, &notif).unwrap();
+        let request = rx.recv().unwrap();
+        let body = request.split("\r\n\r\n").nth(1).unwrap();
+        let signature = sign("secret", body.as_bytes()).unwrap();
+        let header = format!("{}: sha256={}", SIGNATURE_HEADER, signature);
+        assert!(request.to_lowercase().contains(&header.to_lowercase()));
+        assert!(body.contains(r#""event":"BallotCreated""#));
+
+        // HMAC-SHA256 test vector from RFC 4231 (test case 2).
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?").unwrap(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        );
+    }
+
+    #[test]
+    fn test_failed_posts_are_retried_when_due() {
+        let config = sokol_network_config();
+        let notif = threshold_ballot_created(&config);
+        let mut webhook = JsonWebhook::new("secret", 2, vec![]);
+        let now = Utc::now();
+
+        // A failed post is queued rather than retried immediately.
+        let (url, rx) = stand_in_server("500 Internal Server Error", "");
+        assert!(webhook.post(&url, &notif).is_err());
+        assert!(rx.recv().is_ok());
+        let next_attempt_at = webhook.queue_retry(&url, &notif, now).unwrap();
+        assert_eq!(next_attempt_at, now + Duration::seconds(1));
+        assert!(webhook.retry_due(now).is_empty());
+
+        // The stand-in server only accepts one connection, so the first retry fails to connect
+        // and is rescheduled with twice the delay.
+        let retried = webhook.retry_due(next_attempt_at);
+        assert_eq!(retried.len(), 1);
+        assert!(retried[0].result.is_err());
+        let next_attempt_at = retried[0].next_attempt_at.unwrap();
+        assert_eq!(next_attempt_at, now + Duration::seconds(3));
+
+        // The queued request is sent once the webhook is reachable again.
+        let (url, rx) = stand_in_server("200 OK", "");
+        webhook.pending[0].url = url;
+        let retried = webhook.retry_due(next_attempt_at);
+        assert!(retried[0].result.is_ok());
+        assert!(rx.recv().unwrap().contains(r#""event":"BallotCreated""#));
+        assert!(webhook.pending.is_empty());
+
+        // A request is dropped after `max_retries` failed retries.
+        webhook.queue_retry("http://127.0.0.1:1", &notif, now).unwrap();
+        assert!(webhook.retry_all(now)[0].next_attempt_at.is_some());
+        assert!(webhook.retry_all(now)[0].next_attempt_at.is_none());
+        assert!(webhook.pending.is_empty());
+    }
+}